@@ -0,0 +1,208 @@
+use crate::interval::Alignment;
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::mem;
+use std::ops::Add;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+pub trait Aggregator<T> {
+    type Output;
+
+    fn push(&mut self, item: T);
+
+    /// 窗口结束时调用，返回聚合结果并重置状态
+    fn take(&mut self) -> Self::Output;
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Count {
+    count: u64,
+}
+
+impl<T> Aggregator<T> for Count {
+    type Output = u64;
+
+    fn push(&mut self, _item: T) {
+        self.count += 1;
+    }
+
+    fn take(&mut self) -> Self::Output {
+        mem::take(&mut self.count)
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Sum<T> {
+    sum: T,
+}
+
+impl<T: Add<Output = T> + Default> Aggregator<T> for Sum<T> {
+    type Output = T;
+
+    fn push(&mut self, item: T) {
+        self.sum = mem::take(&mut self.sum) + item;
+    }
+
+    fn take(&mut self) -> Self::Output {
+        mem::take(&mut self.sum)
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Min<T> {
+    min: Option<T>,
+}
+
+impl<T> Default for Min<T> {
+    fn default() -> Self {
+        Self { min: None }
+    }
+}
+
+impl<T: PartialOrd> Aggregator<T> for Min<T> {
+    type Output = Option<T>;
+
+    fn push(&mut self, item: T) {
+        if self.min.as_ref().is_none_or(|min| item < *min) {
+            self.min = Some(item);
+        }
+    }
+
+    fn take(&mut self) -> Self::Output {
+        self.min.take()
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Max<T> {
+    max: Option<T>,
+}
+
+impl<T> Default for Max<T> {
+    fn default() -> Self {
+        Self { max: None }
+    }
+}
+
+impl<T: PartialOrd> Aggregator<T> for Max<T> {
+    type Output = Option<T>;
+
+    fn push(&mut self, item: T) {
+        if self.max.as_ref().is_none_or(|max| item > *max) {
+            self.max = Some(item);
+        }
+    }
+
+    fn take(&mut self) -> Self::Output {
+        self.max.take()
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Mean {
+    sum: f64,
+    count: u64,
+}
+
+impl<T: Into<f64>> Aggregator<T> for Mean {
+    type Output = Option<f64>;
+
+    fn push(&mut self, item: T) {
+        self.sum += item.into();
+        self.count += 1;
+    }
+
+    fn take(&mut self) -> Self::Output {
+        let Mean { sum, count } = mem::take(self);
+        (count > 0).then(|| sum / count as f64)
+    }
+}
+
+#[pin_project]
+pub struct AggregateTime<S, A> {
+    period: Duration,
+    aggregator: A,
+    #[pin]
+    stream: Option<S>,
+    started: bool,
+    has_items: bool,
+    alignment: Option<Alignment>,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream, A> Stream for AggregateTime<S, A>
+where
+    A: Aggregator<S::Item>,
+{
+    type Item = A::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let period = self.period;
+        let mut this = self.project();
+
+        // 第一次poll时开始第一个窗口
+        if !*this.started {
+            *this.started = true;
+            let deadline = match this.alignment {
+                Some(alignment) => alignment.next_boundary().1,
+                None => Instant::now() + period,
+            };
+            this.delay.as_mut().reset(deadline);
+        }
+
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    this.aggregator.push(value);
+                    *this.has_items = true;
+                }
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        // stream结束时，未满的窗口有值才输出
+        if this.stream.is_none() {
+            return if mem::take(this.has_items) {
+                Poll::Ready(Some(this.aggregator.take()))
+            } else {
+                Poll::Ready(None)
+            };
+        }
+
+        if this.delay.poll_unpin(cx).is_ready() {
+            let next = match this.alignment {
+                Some(alignment) => alignment.next_boundary().1,
+                None => this.delay.deadline() + period,
+            };
+            this.delay.as_mut().reset(next);
+            *this.has_items = false;
+            return Poll::Ready(Some(this.aggregator.take()));
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<S, A> AggregateTime<S, A> {
+    pub fn new(stream: S, period: Duration, aggregator: A) -> Self {
+        Self {
+            period,
+            aggregator,
+            stream: Some(stream),
+            started: false,
+            has_items: false,
+            alignment: None,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+
+    /// 窗口边界对齐到系统时钟上`period`的整数倍，第一个窗口可能不完整
+    pub fn aligned(mut self) -> Self {
+        self.alignment = Some(Alignment::new(self.period));
+        self
+    }
+}
@@ -0,0 +1,131 @@
+use futures::Stream;
+use pin_project::pin_project;
+use std::collections::VecDeque;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// 丢弃缓冲区中最旧的值
+    DropOldest,
+    /// 丢弃新到达的值
+    DropNewest,
+    /// 停止读取stream，输出完已缓冲的值后产出`Err(BufferOverflow)`并结束
+    Error,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Dropped<T>(pub T);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BufferOverflow;
+
+impl fmt::Display for BufferOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("bounded buffer overflowed")
+    }
+}
+
+impl std::error::Error for BufferOverflow {}
+
+pub type IgnoreDropped<T> = fn(Dropped<T>);
+
+#[pin_project]
+pub struct BufferBounded<S: Stream, F> {
+    capacity: usize,
+    policy: OverflowPolicy,
+    on_drop: F,
+    #[pin]
+    stream: Option<S>,
+    buffer: VecDeque<S::Item>,
+    overflowed: bool,
+}
+
+impl<S: Stream, F> Stream for BufferBounded<S, F>
+where
+    F: FnMut(Dropped<S::Item>),
+{
+    type Item = Result<S::Item, BufferOverflow>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let capacity = self.capacity;
+        let policy = self.policy;
+        let mut this = self.project();
+
+        // 取完stream中已就绪的值，超出容量的按policy丢弃
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    if this.buffer.len() < capacity {
+                        this.buffer.push_back(value);
+                        continue;
+                    }
+
+                    match policy {
+                        OverflowPolicy::DropOldest => {
+                            if let Some(oldest) = this.buffer.pop_front() {
+                                (this.on_drop)(Dropped(oldest));
+                            }
+                            this.buffer.push_back(value);
+                        }
+                        OverflowPolicy::DropNewest => (this.on_drop)(Dropped(value)),
+                        OverflowPolicy::Error => {
+                            (this.on_drop)(Dropped(value));
+                            *this.overflowed = true;
+                            this.stream.set(None);
+                        }
+                    }
+                }
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        if let Some(value) = this.buffer.pop_front() {
+            return Poll::Ready(Some(Ok(value)));
+        }
+
+        if *this.overflowed {
+            *this.overflowed = false;
+            return Poll::Ready(Some(Err(BufferOverflow)));
+        }
+
+        if this.stream.is_none() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S: Stream> BufferBounded<S, IgnoreDropped<S::Item>> {
+    pub fn new(stream: S, capacity: usize, policy: OverflowPolicy) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            policy,
+            on_drop: |_| {},
+            stream: Some(stream),
+            // 按需增长到capacity，不预先分配
+            buffer: VecDeque::new(),
+            overflowed: false,
+        }
+    }
+}
+
+impl<S: Stream, F> BufferBounded<S, F> {
+    /// 每个被丢弃的值都会传给`on_drop`，可以用来计数
+    pub fn on_drop<G>(self, on_drop: G) -> BufferBounded<S, G>
+    where
+        G: FnMut(Dropped<S::Item>),
+    {
+        BufferBounded {
+            capacity: self.capacity,
+            policy: self.policy,
+            on_drop,
+            stream: self.stream,
+            buffer: self.buffer,
+            overflowed: self.overflowed,
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use std::future::Future;
+use std::ops::{Add, Sub};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+#[cfg(not(any(
+    feature = "rt-tokio",
+    feature = "rt-async-io",
+    feature = "rt-futures-timer"
+)))]
+compile_error!(
+    "one of the features `rt-tokio`, `rt-async-io` or `rt-futures-timer` must be enabled"
+);
+
+// 不同的后端有不同的`Instant`和计时器，feature合并时不能悄悄换成另一个
+#[cfg(any(
+    all(feature = "rt-tokio", feature = "rt-async-io"),
+    all(feature = "rt-tokio", feature = "rt-futures-timer"),
+    all(feature = "rt-async-io", feature = "rt-futures-timer")
+))]
+compile_error!(
+    "only one of the features `rt-tokio`, `rt-async-io` or `rt-futures-timer` can be enabled, \
+     disable the default features to use `rt-async-io` or `rt-futures-timer`"
+);
+
+pub trait Clock {
+    type Instant: Copy + Ord + Add<Duration, Output = Self::Instant> + Sub<Output = Duration>;
+    /// `Sleep`的输出会被忽略，只关心它是否已经到期
+    type Sleep: Future;
+
+    fn now(&self) -> Self::Instant;
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep;
+
+    /// 重置一个已有的`Sleep`，避免每次重新分配
+    fn reset(&self, sleep: Pin<&mut Self::Sleep>, deadline: Self::Instant);
+}
+
+/// 由feature选择的默认时钟
+#[cfg(feature = "rt-tokio")]
+pub type DefaultClock = TokioClock;
+// 同时开启多个后端时已经报错，这里的`not`只是为了不再多报一个重复定义
+#[cfg(all(feature = "rt-async-io", not(feature = "rt-tokio")))]
+pub type DefaultClock = AsyncIoClock;
+#[cfg(all(
+    feature = "rt-futures-timer",
+    not(feature = "rt-tokio"),
+    not(feature = "rt-async-io")
+))]
+pub type DefaultClock = FuturesTimerClock;
+
+pub type Instant = <DefaultClock as Clock>::Instant;
+
+#[cfg(feature = "rt-tokio")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TokioClock;
+
+#[cfg(feature = "rt-tokio")]
+impl Clock for TokioClock {
+    type Instant = tokio::time::Instant;
+    type Sleep = tokio::time::Sleep;
+
+    fn now(&self) -> Self::Instant {
+        tokio::time::Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep {
+        tokio::time::sleep_until(deadline)
+    }
+
+    fn reset(&self, sleep: Pin<&mut Self::Sleep>, deadline: Self::Instant) {
+        sleep.reset(deadline);
+    }
+}
+
+#[cfg(feature = "rt-async-io")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct AsyncIoClock;
+
+#[cfg(feature = "rt-async-io")]
+impl Clock for AsyncIoClock {
+    type Instant = std::time::Instant;
+    type Sleep = async_io::Timer;
+
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep {
+        async_io::Timer::at(deadline)
+    }
+
+    fn reset(&self, sleep: Pin<&mut Self::Sleep>, deadline: Self::Instant) {
+        sleep.get_mut().set_at(deadline);
+    }
+}
+
+#[cfg(feature = "rt-futures-timer")]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct FuturesTimerClock;
+
+#[cfg(feature = "rt-futures-timer")]
+impl Clock for FuturesTimerClock {
+    type Instant = std::time::Instant;
+    type Sleep = futures_timer::Delay;
+
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep {
+        futures_timer::Delay::new(deadline.saturating_duration_since(self.now()))
+    }
+
+    fn reset(&self, sleep: Pin<&mut Self::Sleep>, deadline: Self::Instant) {
+        let duration = deadline.saturating_duration_since(self.now());
+        sleep.get_mut().reset(duration);
+    }
+}
+
+/// 只有调用`advance`时才会前进的时钟，用于在测试里控制时间
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    state: Arc<Mutex<ManualState>>,
+}
+
+#[derive(Debug)]
+struct ManualState {
+    now: std::time::Instant,
+    wakers: Vec<Waker>,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(ManualState {
+                now: std::time::Instant::now(),
+                wakers: Vec::new(),
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ManualState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// 推进时间，并唤醒所有等待中的`ManualSleep`
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.lock();
+        state.now += duration;
+        let wakers = std::mem::take(&mut state.wakers);
+        drop(state);
+
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug)]
+pub struct ManualSleep {
+    clock: ManualClock,
+    deadline: std::time::Instant,
+}
+
+impl Future for ManualSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.clock.lock();
+        if state.now >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            state.wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+impl Clock for ManualClock {
+    type Instant = std::time::Instant;
+    type Sleep = ManualSleep;
+
+    fn now(&self) -> Self::Instant {
+        self.lock().now
+    }
+
+    fn sleep_until(&self, deadline: Self::Instant) -> Self::Sleep {
+        ManualSleep {
+            clock: self.clone(),
+            deadline,
+        }
+    }
+
+    fn reset(&self, sleep: Pin<&mut Self::Sleep>, deadline: Self::Instant) {
+        sleep.get_mut().deadline = deadline;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::task::{waker, ArcWake};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Default)]
+    struct Flag(AtomicBool);
+
+    impl ArcWake for Flag {
+        fn wake_by_ref(arc_self: &Arc<Self>) {
+            arc_self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn manual_sleep_wakes_on_advance() {
+        let clock = ManualClock::new();
+        let mut sleep = clock.sleep_until(clock.now() + Duration::from_millis(100));
+        let flag = Arc::new(Flag::default());
+        let waker = waker(flag.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        assert!(Pin::new(&mut sleep).poll(&mut cx).is_pending());
+        clock.advance(Duration::from_millis(99));
+        assert!(flag.0.swap(false, Ordering::SeqCst));
+        assert!(Pin::new(&mut sleep).poll(&mut cx).is_pending());
+        clock.advance(Duration::from_millis(1));
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert!(Pin::new(&mut sleep).poll(&mut cx).is_ready());
+
+        clock.reset(
+            Pin::new(&mut sleep),
+            clock.now() + Duration::from_millis(10),
+        );
+        assert!(Pin::new(&mut sleep).poll(&mut cx).is_pending());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+fn poll_latest<S: Stream>(
+    mut stream: Pin<&mut Option<S>>,
+    latest: &mut Option<S::Item>,
+    cx: &mut Context<'_>,
+) -> bool {
+    let mut received = false;
+    while let Some(pin_stream) = stream.as_mut().as_pin_mut() {
+        match pin_stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                *latest = Some(value);
+                received = true;
+            }
+            Poll::Ready(None) => stream.set(None),
+            Poll::Pending => break,
+        }
+    }
+    received
+}
+
+// 最多取一个值，stream结束时置为`None`
+fn poll_one<S: Stream>(
+    mut stream: Pin<&mut Option<S>>,
+    latest: &mut Option<S::Item>,
+    cx: &mut Context<'_>,
+) -> bool {
+    let Some(pin_stream) = stream.as_mut().as_pin_mut() else {
+        return false;
+    };
+
+    match pin_stream.poll_next(cx) {
+        Poll::Ready(Some(value)) => {
+            *latest = Some(value);
+            true
+        }
+        Poll::Ready(None) => {
+            stream.set(None);
+            false
+        }
+        Poll::Pending => false,
+    }
+}
+
+#[pin_project]
+pub struct WithLatestFrom<S, O: Stream> {
+    #[pin]
+    stream: S,
+    #[pin]
+    other: Option<O>,
+    latest: Option<O::Item>,
+}
+
+impl<S: Stream, O: Stream> Stream for WithLatestFrom<S, O>
+where
+    O::Item: Clone,
+{
+    type Item = (S::Item, O::Item);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        poll_latest(this.other.as_mut(), this.latest, cx);
+
+        // other结束后沿用最后一个值；other没有产出过值就结束，则不会再有输出
+        if this.other.is_none() && this.latest.is_none() {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    // other还没有值时丢弃
+                    if let Some(latest) = this.latest.as_ref() {
+                        break Poll::Ready(Some((value, latest.clone())));
+                    }
+                }
+                Poll::Ready(None) => break Poll::Ready(None),
+                Poll::Pending => break Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<S, O: Stream> WithLatestFrom<S, O> {
+    pub fn new(stream: S, other: O) -> Self {
+        Self {
+            stream,
+            other: Some(other),
+            latest: None,
+        }
+    }
+}
+
+/// 两边都有值之后，任何一边的每次更新都会产出一个组合
+///
+/// 一边结束后沿用它最后的值，另一边的更新照常产出；
+/// 两边都结束，或者其中一边没有产出过值就结束时，stream结束
+#[pin_project]
+pub struct CombineLatest<A: Stream, B: Stream> {
+    #[pin]
+    a: Option<A>,
+    #[pin]
+    b: Option<B>,
+    latest_a: Option<A::Item>,
+    latest_b: Option<B::Item>,
+    // 轮流先poll哪一边，避免一边一直抢占
+    b_first: bool,
+}
+
+fn combine_terminated<A, B, TA, TB>(
+    a: &Option<A>,
+    b: &Option<B>,
+    latest_a: &Option<TA>,
+    latest_b: &Option<TB>,
+) -> bool {
+    (a.is_none() && b.is_none())
+        || (a.is_none() && latest_a.is_none())
+        || (b.is_none() && latest_b.is_none())
+}
+
+impl<A: Stream, B: Stream> Stream for CombineLatest<A, B>
+where
+    A::Item: Clone,
+    B::Item: Clone,
+{
+    type Item = (A::Item, B::Item);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            let mut received_any = false;
+
+            for _ in 0..2 {
+                let received = if *this.b_first {
+                    poll_one(this.b.as_mut(), this.latest_b, cx)
+                } else {
+                    poll_one(this.a.as_mut(), this.latest_a, cx)
+                };
+                *this.b_first = !*this.b_first;
+
+                if received {
+                    if let (Some(a), Some(b)) = (this.latest_a.as_ref(), this.latest_b.as_ref()) {
+                        return Poll::Ready(Some((a.clone(), b.clone())));
+                    }
+                    // 另一边还没有值，继续poll
+                    received_any = true;
+                }
+            }
+
+            if combine_terminated(&*this.a, &*this.b, this.latest_a, this.latest_b) {
+                break Poll::Ready(None);
+            }
+            if !received_any {
+                break Poll::Pending;
+            }
+        }
+    }
+}
+
+impl<A: Stream, B: Stream> CombineLatest<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        Self {
+            a: Some(a),
+            b: Some(b),
+            latest_a: None,
+            latest_b: None,
+            b_first: false,
+        }
+    }
+}
+
+/// 与`CombineLatest`相同，但连续的更新只在安静`duration`之后产出最后一个组合
+///
+/// 结束的规则与`CombineLatest`相同，结束时立即产出挂起的组合
+#[pin_project]
+pub struct CombineLatestDebounced<A: Stream, B: Stream> {
+    duration: Duration,
+    #[pin]
+    a: Option<A>,
+    #[pin]
+    b: Option<B>,
+    latest_a: Option<A::Item>,
+    latest_b: Option<B::Item>,
+    has_pending: bool,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<A: Stream, B: Stream> Stream for CombineLatestDebounced<A, B>
+where
+    A::Item: Clone,
+    B::Item: Clone,
+{
+    type Item = (A::Item, B::Item);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let duration = self.duration;
+        let mut this = self.project();
+
+        let received_a = poll_latest(this.a.as_mut(), this.latest_a, cx);
+        let received_b = poll_latest(this.b.as_mut(), this.latest_b, cx);
+
+        let both_present = this.latest_a.is_some() && this.latest_b.is_some();
+        if (received_a || received_b) && both_present {
+            *this.has_pending = true;
+            this.delay.as_mut().reset(Instant::now() + duration);
+        }
+
+        let terminated = combine_terminated(&*this.a, &*this.b, this.latest_a, this.latest_b);
+
+        // 结束时立即返回挂起的组合
+        if *this.has_pending && (terminated || this.delay.poll_unpin(cx).is_ready()) {
+            *this.has_pending = false;
+            if let (Some(a), Some(b)) = (this.latest_a.as_ref(), this.latest_b.as_ref()) {
+                return Poll::Ready(Some((a.clone(), b.clone())));
+            }
+        }
+
+        if terminated {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<A: Stream, B: Stream> CombineLatestDebounced<A, B> {
+    pub fn new(a: A, b: B, duration: Duration) -> Self {
+        Self {
+            duration,
+            a: Some(a),
+            b: Some(b),
+            latest_a: None,
+            latest_b: None,
+            has_pending: false,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+    use futures::{stream, StreamExt};
+
+    #[test]
+    fn combine_latest_emits_every_update() {
+        let combined = CombineLatest::new(stream::iter([1, 2, 3]), stream::iter(['x', 'y']));
+        let items: Vec<_> = block_on(combined.collect());
+        assert_eq!(items, vec![(1, 'x'), (2, 'x'), (2, 'y'), (3, 'y')]);
+    }
+
+    #[test]
+    fn combine_latest_keeps_last_value_of_ended_side() {
+        let combined = CombineLatest::new(stream::iter([1]), stream::iter(['x', 'y', 'z']));
+        let items: Vec<_> = block_on(combined.collect());
+        assert_eq!(items, vec![(1, 'x'), (1, 'y'), (1, 'z')]);
+    }
+
+    #[test]
+    fn combine_latest_ends_when_a_side_ends_empty() {
+        let combined = CombineLatest::new(stream::iter([1, 2]), stream::empty::<char>());
+        let items: Vec<_> = block_on(combined.collect());
+        assert!(items.is_empty());
+    }
+}
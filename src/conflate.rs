@@ -0,0 +1,215 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+pub type KeepLatest<T> = fn(T, T) -> T;
+
+#[pin_project]
+pub struct Conflate<S: Stream, F> {
+    merge_fn: F,
+    #[pin]
+    stream: Option<S>,
+    pending: Option<S::Item>,
+}
+
+impl<S: Stream, F> Stream for Conflate<S, F>
+where
+    F: FnMut(S::Item, S::Item) -> S::Item,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        // 每次被poll时取完stream中已就绪的值，合并成一个
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    let merged = match this.pending.take() {
+                        Some(pending) => (this.merge_fn)(pending, value),
+                        None => value,
+                    };
+                    *this.pending = Some(merged);
+                }
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        match this.pending.take() {
+            Some(value) => Poll::Ready(Some(value)),
+            None if this.stream.is_none() => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<S: Stream, F> Conflate<S, F> {
+    pub fn new(stream: S, merge_fn: F) -> Self {
+        Self {
+            merge_fn,
+            stream: Some(stream),
+            pending: None,
+        }
+    }
+}
+
+impl<S: Stream> Conflate<S, KeepLatest<S::Item>> {
+    pub fn latest(stream: S) -> Self {
+        Self::new(stream, |_, latest| latest)
+    }
+}
+
+#[pin_project]
+pub struct CoalesceByKey<S: Stream, KeyFn, K> {
+    period: Duration,
+    key_fn: KeyFn,
+    #[pin]
+    stream: Option<S>,
+    // 上次tick之后有更新的key，按第一次更新的顺序输出
+    indices: HashMap<K, usize>,
+    updated: Vec<(K, S::Item)>,
+    // 每个key最后输出的值，值没有变化的更新不再输出
+    emitted: HashMap<K, S::Item>,
+    started: bool,
+    delay: Pin<Box<Sleep>>,
+}
+
+// 取出上次tick之后真正变化了的值
+fn take_changed<K: Hash + Eq, T: PartialEq + Clone>(
+    indices: &mut HashMap<K, usize>,
+    updated: &mut Vec<(K, T)>,
+    emitted: &mut HashMap<K, T>,
+) -> Vec<T> {
+    indices.clear();
+    updated
+        .drain(..)
+        .filter_map(|(key, value)| {
+            if emitted.get(&key) == Some(&value) {
+                return None;
+            }
+            emitted.insert(key, value.clone());
+            Some(value)
+        })
+        .collect()
+}
+
+impl<S: Stream, KeyFn, K> Stream for CoalesceByKey<S, KeyFn, K>
+where
+    S::Item: PartialEq + Clone,
+    KeyFn: FnMut(&S::Item) -> K,
+    K: Hash + Eq + Clone,
+{
+    type Item = Vec<S::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let period = self.period;
+        let mut this = self.project();
+
+        if !*this.started {
+            *this.started = true;
+            this.delay.as_mut().reset(Instant::now() + period);
+        }
+
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    let key = (this.key_fn)(&value);
+                    match this.indices.get(&key) {
+                        Some(&index) => this.updated[index].1 = value,
+                        None => {
+                            this.indices.insert(key.clone(), this.updated.len());
+                            this.updated.push((key, value));
+                        }
+                    }
+                }
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        // stream结束时立即输出剩余的更新
+        if this.stream.is_none() {
+            let changed = take_changed(this.indices, this.updated, this.emitted);
+            return if changed.is_empty() {
+                Poll::Ready(None)
+            } else {
+                Poll::Ready(Some(changed))
+            };
+        }
+
+        if this.delay.poll_unpin(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        // 从当前时间算起，停顿之后不会逐个补上错过的tick
+        this.delay.as_mut().reset(Instant::now() + period);
+
+        let changed = take_changed(this.indices, this.updated, this.emitted);
+        if changed.is_empty() {
+            // 没有变化的tick不输出，等待下一个tick
+            let _ = this.delay.poll_unpin(cx);
+            Poll::Pending
+        } else {
+            Poll::Ready(Some(changed))
+        }
+    }
+}
+
+impl<S: Stream, KeyFn, K> CoalesceByKey<S, KeyFn, K> {
+    pub fn new(stream: S, period: Duration, key_fn: KeyFn) -> Self {
+        Self {
+            period,
+            key_fn,
+            stream: Some(stream),
+            indices: HashMap::new(),
+            updated: Vec::new(),
+            emitted: HashMap::new(),
+            started: false,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::block_on;
+    use futures::{stream, StreamExt};
+
+    #[test]
+    fn coalesce_by_key_skips_unchanged_values() {
+        // 每批之间间隔几个tick
+        let batches = vec![
+            vec![("a", 1), ("b", 1)],
+            vec![("a", 1), ("b", 2)],
+            vec![("a", 2), ("a", 1)],
+            vec![("b", 3)],
+        ];
+        let source = stream::iter(batches)
+            .then(|batch| async move {
+                sleep(Duration::from_millis(50)).await;
+                stream::iter(batch)
+            })
+            .flatten();
+
+        let items: Vec<_> = block_on(async {
+            CoalesceByKey::new(
+                source,
+                Duration::from_millis(20),
+                |&(key, _): &(&'static str, i32)| key,
+            )
+            .collect()
+            .await
+        });
+        assert_eq!(
+            items,
+            vec![vec![("a", 1), ("b", 1)], vec![("b", 2)], vec![("b", 3)]]
+        );
+    }
+}
@@ -1,22 +1,25 @@
-use futures::stream::Stream;
-use futures::FutureExt;
+use crate::clock::{Clock, DefaultClock};
+use crate::time::{sleep, Instant, Sleep};
+use futures::stream::{Fuse, Stream};
+use futures::{FutureExt, StreamExt};
 use pin_project::pin_project;
 use std::future::Future;
+use std::mem;
 use std::pin::Pin;
 use std::task::Poll;
 use std::time::Duration;
-use tokio::time::{sleep, Instant, Sleep};
 
 #[pin_project]
-pub struct DebounceTimeFilter<S: Stream> {
+pub struct DebounceTimeFilter<S: Stream, C: Clock = DefaultClock> {
     duration: Duration,
+    clock: C,
     #[pin]
     stream: Option<S>,
     last_value: Option<S::Item>,
-    delay: Pin<Box<Sleep>>,
+    delay: Pin<Box<C::Sleep>>,
 }
 
-impl<S: Stream> Stream for DebounceTimeFilter<S> {
+impl<S: Stream, C: Clock> Stream for DebounceTimeFilter<S, C> {
     type Item = S::Item;
 
     fn poll_next(
@@ -27,6 +30,7 @@ impl<S: Stream> Stream for DebounceTimeFilter<S> {
         let mut this = self.project();
 
         loop {
+            let clock = &*this.clock;
             let mut delay = this.delay.as_mut();
             let last_value = &mut *this.last_value;
             let mut stream = this.stream.as_mut();
@@ -44,7 +48,7 @@ impl<S: Stream> Stream for DebounceTimeFilter<S> {
                 // 从stream中获取值，替换掉挂起的值
                 match pin_stream.poll_next(cx) {
                     Poll::Ready(Some(value)) => {
-                        delay.as_mut().reset(Instant::now() + duration);
+                        clock.reset(delay.as_mut(), clock.now() + duration);
                         *last_value = Some(value);
                         if poll_res.is_pending() {
                             continue;
@@ -70,11 +74,18 @@ impl<S: Stream> Stream for DebounceTimeFilter<S> {
 
 impl<S: Stream> DebounceTimeFilter<S> {
     pub fn new(stream: S, duration: Duration) -> Self {
+        Self::with_clock(stream, duration, DefaultClock::default())
+    }
+}
+
+impl<S: Stream, C: Clock> DebounceTimeFilter<S, C> {
+    pub fn with_clock(stream: S, duration: Duration, clock: C) -> Self {
         Self {
             stream: Some(stream),
-            delay: Box::pin(sleep(Duration::from_nanos(0))),
+            delay: Box::pin(clock.sleep_until(clock.now())),
             last_value: None,
             duration,
+            clock,
         }
     }
 }
@@ -83,15 +94,16 @@ impl<S: Stream> DebounceTimeFilter<S> {
 pub struct Debounced<T>(pub T);
 
 #[pin_project]
-pub struct DebounceTime<S: Stream> {
+pub struct DebounceTime<S: Stream, C: Clock = DefaultClock> {
     duration: Duration,
+    clock: C,
     #[pin]
     stream: Option<S>,
     last_value: Option<S::Item>,
-    delay: Pin<Box<Sleep>>,
+    delay: Pin<Box<C::Sleep>>,
 }
 
-impl<S: Stream> Stream for DebounceTime<S> {
+impl<S: Stream, C: Clock> Stream for DebounceTime<S, C> {
     type Item = Result<S::Item, Debounced<S::Item>>;
 
     fn poll_next(
@@ -102,6 +114,7 @@ impl<S: Stream> Stream for DebounceTime<S> {
         let mut this = self.project();
 
         loop {
+            let clock = &*this.clock;
             let mut delay = this.delay.as_mut();
             let last_value = &mut* this.last_value;
             let mut stream = this.stream.as_mut();
@@ -118,7 +131,7 @@ impl<S: Stream> Stream for DebounceTime<S> {
             if let Some(pin_stream) = stream.as_mut().as_pin_mut() {
                 match pin_stream.poll_next(cx) {
                     Poll::Ready(Some(value)) => {
-                        delay.as_mut().reset(Instant::now() + duration);
+                        clock.reset(delay.as_mut(), clock.now() + duration);
                         if let Some(debounced) = last_value.replace(value) {
                             poll_res = Poll::Ready(Some(Err(Debounced(debounced))));
                         }
@@ -147,11 +160,18 @@ impl<S: Stream> Stream for DebounceTime<S> {
 
 impl<S: Stream> DebounceTime<S> {
     pub fn new(stream: S, duration: Duration) -> Self {
+        Self::with_clock(stream, duration, DefaultClock::default())
+    }
+}
+
+impl<S: Stream, C: Clock> DebounceTime<S, C> {
+    pub fn with_clock(stream: S, duration: Duration, clock: C) -> Self {
         Self {
             stream: Some(stream),
-            delay: Box::pin(sleep(Duration::from_nanos(0))),
+            delay: Box::pin(clock.sleep_until(clock.now())),
             last_value: None,
             duration,
+            clock,
         }
     }
 }
@@ -316,3 +336,209 @@ impl<S: Stream, Selector, Fut> Debounce<S, Selector, Fut> {
         }
     }
 }
+
+pub trait MergeSources {
+    type Fused;
+    type Output;
+
+    fn fuse(self) -> Self::Fused;
+
+    fn empty(fused: &Self::Fused) -> Self::Output;
+
+    /// 轮询所有未结束的stream并记录各自最新的值，返回(是否收到新值, 是否全部结束)
+    fn poll_sources(
+        fused: &mut Self::Fused,
+        latest: &mut Self::Output,
+        cx: &mut std::task::Context<'_>,
+    ) -> (bool, bool);
+}
+
+fn poll_source<S: Stream>(
+    mut stream: Pin<&mut Fuse<S>>,
+    latest: &mut Option<S::Item>,
+    cx: &mut std::task::Context<'_>,
+) -> bool {
+    let mut received = false;
+    while !stream.is_done() {
+        match stream.as_mut().poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                *latest = Some(value);
+                received = true;
+            }
+            _ => break,
+        }
+    }
+    received
+}
+
+impl<S: Stream> MergeSources for Vec<S> {
+    type Fused = Vec<Pin<Box<Fuse<S>>>>;
+    type Output = Vec<Option<S::Item>>;
+
+    fn fuse(self) -> Self::Fused {
+        self.into_iter()
+            .map(|stream| Box::pin(stream.fuse()))
+            .collect()
+    }
+
+    fn empty(fused: &Self::Fused) -> Self::Output {
+        fused.iter().map(|_| None).collect()
+    }
+
+    fn poll_sources(
+        fused: &mut Self::Fused,
+        latest: &mut Self::Output,
+        cx: &mut std::task::Context<'_>,
+    ) -> (bool, bool) {
+        let mut received = false;
+        let mut terminated = true;
+        for (stream, latest) in fused.iter_mut().zip(latest.iter_mut()) {
+            received |= poll_source(stream.as_mut(), latest, cx);
+            terminated &= stream.is_done();
+        }
+        (received, terminated)
+    }
+}
+
+macro_rules! merge_sources_tuple {
+    ($($S:ident $idx:tt),+) => {
+        impl<$($S: Stream),+> MergeSources for ($($S,)+) {
+            type Fused = ($(Pin<Box<Fuse<$S>>>,)+);
+            type Output = ($(Option<<$S as Stream>::Item>,)+);
+
+            fn fuse(self) -> Self::Fused {
+                ($(Box::pin(self.$idx.fuse()),)+)
+            }
+
+            fn empty(_fused: &Self::Fused) -> Self::Output {
+                ($(None::<<$S as Stream>::Item>,)+)
+            }
+
+            fn poll_sources(
+                fused: &mut Self::Fused,
+                latest: &mut Self::Output,
+                cx: &mut std::task::Context<'_>,
+            ) -> (bool, bool) {
+                let mut received = false;
+                let mut terminated = true;
+                $(
+                    received |= poll_source(fused.$idx.as_mut(), &mut latest.$idx, cx);
+                    terminated &= fused.$idx.is_done();
+                )+
+                (received, terminated)
+            }
+        }
+    };
+}
+
+merge_sources_tuple!(A 0, B 1);
+merge_sources_tuple!(A 0, B 1, C 2);
+merge_sources_tuple!(A 0, B 1, C 2, D 3);
+merge_sources_tuple!(A 0, B 1, C 2, D 3, E 4);
+merge_sources_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+merge_sources_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+merge_sources_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+pub fn debounce_merge<Sources: MergeSources>(
+    sources: Sources,
+    duration: Duration,
+) -> DebounceMerge<Sources> {
+    DebounceMerge::new(sources, duration)
+}
+
+#[pin_project]
+pub struct DebounceMerge<Sources: MergeSources> {
+    duration: Duration,
+    sources: Sources::Fused,
+    latest: Sources::Output,
+    has_pending: bool,
+    terminated: bool,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<Sources: MergeSources> Stream for DebounceMerge<Sources> {
+    type Item = Sources::Output;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let duration = self.duration;
+        let this = self.project();
+
+        if *this.terminated {
+            return Poll::Ready(None);
+        }
+
+        let (received, all_terminated) = Sources::poll_sources(this.sources, this.latest, cx);
+        if received {
+            *this.has_pending = true;
+            this.delay.as_mut().reset(Instant::now() + duration);
+        }
+
+        // 所有stream都结束了，立即返回挂起的值
+        if all_terminated {
+            *this.terminated = true;
+        }
+
+        if *this.has_pending && (all_terminated || this.delay.poll_unpin(cx).is_ready()) {
+            *this.has_pending = false;
+            let empty = Sources::empty(this.sources);
+            return Poll::Ready(Some(mem::replace(this.latest, empty)));
+        }
+
+        if all_terminated {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<Sources: MergeSources> DebounceMerge<Sources> {
+    pub fn new(sources: Sources, duration: Duration) -> Self {
+        let sources = sources.fuse();
+        Self {
+            duration,
+            latest: Sources::empty(&sources),
+            sources,
+            has_pending: false,
+            terminated: false,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use futures::channel::mpsc;
+
+    #[test]
+    fn debounce_time_with_manual_clock() {
+        let clock = ManualClock::new();
+        let (tx, rx) = mpsc::unbounded();
+        let mut debounced = DebounceTime::with_clock(rx, Duration::from_millis(100), clock.clone());
+
+        tx.unbounded_send(1).unwrap();
+        assert!(debounced.next().now_or_never().is_none());
+
+        clock.advance(Duration::from_millis(50));
+        tx.unbounded_send(2).unwrap();
+        assert!(matches!(
+            debounced.next().now_or_never(),
+            Some(Some(Err(Debounced(1))))
+        ));
+        assert!(debounced.next().now_or_never().is_none());
+
+        // 从最后一个值开始重新计时
+        clock.advance(Duration::from_millis(99));
+        assert!(debounced.next().now_or_never().is_none());
+        clock.advance(Duration::from_millis(1));
+        assert!(matches!(debounced.next().now_or_never(), Some(Some(Ok(2)))));
+
+        drop(tx);
+        assert!(matches!(debounced.next().now_or_never(), Some(None)));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::time::Instant;
+use futures::Stream;
+use pin_project::pin_project;
+use std::collections::{HashSet, VecDeque};
+use std::hash::Hash;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone)]
+pub struct Deduped<T>(pub T);
+
+#[pin_project]
+pub struct DedupeWithin<S, KeyFn, K> {
+    #[pin]
+    stream: S,
+    ttl: Duration,
+    key_fn: KeyFn,
+    seen: HashSet<K>,
+    // 按到期时间排序，用于淘汰过期的key
+    expirations: VecDeque<(Instant, K)>,
+}
+
+impl<S: Stream, KeyFn, K> Stream for DedupeWithin<S, KeyFn, K>
+where
+    KeyFn: FnMut(&S::Item) -> K,
+    K: Hash + Eq + Clone,
+{
+    type Item = Result<S::Item, Deduped<S::Item>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let ttl = self.ttl;
+        let this = self.project();
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                let now = Instant::now();
+                while let Some((expire_at, _)) = this.expirations.front() {
+                    if *expire_at > now {
+                        break;
+                    }
+                    if let Some((_, key)) = this.expirations.pop_front() {
+                        this.seen.remove(&key);
+                    }
+                }
+
+                let key = (this.key_fn)(&value);
+                if this.seen.contains(&key) {
+                    return Poll::Ready(Some(Err(Deduped(value))));
+                }
+
+                this.seen.insert(key.clone());
+                this.expirations.push_back((now + ttl, key));
+                Poll::Ready(Some(Ok(value)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S, KeyFn, K> DedupeWithin<S, KeyFn, K> {
+    pub fn new(stream: S, ttl: Duration, key_fn: KeyFn) -> Self {
+        Self {
+            stream,
+            ttl,
+            key_fn,
+            seen: HashSet::new(),
+            expirations: VecDeque::new(),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::stream::FuturesUnordered;
+use futures::{FutureExt, Stream, StreamExt};
+use pin_project::pin_project;
+use std::collections::{BTreeMap, VecDeque};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+const DEFAULT_MAX_BUFFER: usize = 1024;
+
+#[pin_project]
+pub struct Delay<S: Stream> {
+    duration: Duration,
+    max_buffer: usize,
+    #[pin]
+    stream: Option<S>,
+    buffer: VecDeque<(Instant, S::Item)>,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream> Stream for Delay<S> {
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let duration = self.duration;
+        let max_buffer = self.max_buffer;
+        let mut this = self.project();
+
+        // 缓冲区满了就不再从stream中取值，直到有值被释放
+        while this.buffer.len() < max_buffer {
+            let Some(pin_stream) = this.stream.as_mut().as_pin_mut() else {
+                break;
+            };
+
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    this.buffer.push_back((Instant::now() + duration, value));
+                }
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        let Some(&(due, _)) = this.buffer.front() else {
+            // stream结束且缓冲区已清空
+            return if this.stream.is_none() {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            };
+        };
+
+        let mut delay = this.delay.as_mut();
+        if delay.deadline() != due {
+            delay.as_mut().reset(due);
+        }
+
+        if delay.poll_unpin(cx).is_ready() {
+            Poll::Ready(this.buffer.pop_front().map(|(_, value)| value))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S: Stream> Delay<S> {
+    pub fn new(stream: S, duration: Duration) -> Self {
+        Self {
+            duration,
+            max_buffer: DEFAULT_MAX_BUFFER,
+            stream: Some(stream),
+            buffer: VecDeque::new(),
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+
+    /// 最多缓存的值的数量，默认为1024
+    pub fn with_max_buffer(mut self, max_buffer: usize) -> Self {
+        self.max_buffer = max_buffer.max(1);
+        self
+    }
+}
+
+#[pin_project]
+struct Delayed<Fut, T> {
+    #[pin]
+    delayer: Fut,
+    index: u64,
+    value: Option<T>,
+}
+
+impl<Fut: Future, T> Future for Delayed<Fut, T> {
+    type Output = (u64, T);
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        match this.delayer.poll(cx) {
+            Poll::Ready(_) => Poll::Ready((
+                *this.index,
+                this.value.take().expect("Delayed polled after completion"),
+            )),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[pin_project]
+pub struct DelayWhen<S: Stream, Selector, Fut> {
+    selector: Selector,
+    #[pin]
+    stream: Option<S>,
+    delayers: FuturesUnordered<Delayed<Fut, S::Item>>,
+    arrival_order: bool,
+    next_index: u64,
+    next_emit: u64,
+    // 按到达顺序输出时，已完成但还没轮到的值
+    finished: BTreeMap<u64, S::Item>,
+}
+
+impl<S: Stream, Selector, Fut> Stream for DelayWhen<S, Selector, Fut>
+where
+    Selector: FnMut(&S::Item) -> Fut,
+    Fut: Future,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    let delayer = (this.selector)(&value);
+                    this.delayers.push(Delayed {
+                        delayer,
+                        index: *this.next_index,
+                        value: Some(value),
+                    });
+                    *this.next_index += 1;
+                }
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        if !*this.arrival_order {
+            if let Poll::Ready(Some((_, value))) = this.delayers.poll_next_unpin(cx) {
+                return Poll::Ready(Some(value));
+            }
+        } else {
+            loop {
+                if let Some(value) = this.finished.remove(this.next_emit) {
+                    *this.next_emit += 1;
+                    return Poll::Ready(Some(value));
+                }
+
+                match this.delayers.poll_next_unpin(cx) {
+                    Poll::Ready(Some((index, value))) => {
+                        this.finished.insert(index, value);
+                    }
+                    Poll::Ready(None) | Poll::Pending => break,
+                }
+            }
+        }
+
+        // stream结束且没有等待中的值
+        if this.stream.is_none() && this.delayers.is_empty() && this.finished.is_empty() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S: Stream, Selector, Fut> DelayWhen<S, Selector, Fut> {
+    pub fn new(stream: S, selector: Selector) -> Self {
+        Self {
+            selector,
+            stream: Some(stream),
+            delayers: FuturesUnordered::new(),
+            arrival_order: false,
+            next_index: 0,
+            next_emit: 0,
+            finished: BTreeMap::new(),
+        }
+    }
+
+    /// 按值到达的顺序输出，而不是按各自future完成的顺序
+    pub fn with_arrival_order(mut self) -> Self {
+        self.arrival_order = true;
+        self
+    }
+}
@@ -0,0 +1,73 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+pub type CloneKey<T> = fn(&T) -> T;
+
+#[pin_project]
+pub struct DistinctUntilChangedWithRefresh<S: Stream, KeyFn, K> {
+    refresh: Duration,
+    key_fn: KeyFn,
+    #[pin]
+    stream: S,
+    last_key: Option<K>,
+    // 最近一个值（包括被去重掉的），到时间后重新发出
+    last_value: Option<S::Item>,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream, KeyFn, K> Stream for DistinctUntilChangedWithRefresh<S, KeyFn, K>
+where
+    S::Item: Clone,
+    KeyFn: FnMut(&S::Item) -> K,
+    K: PartialEq,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let refresh = self.refresh;
+        let mut this = self.project();
+
+        loop {
+            match this.stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    let key = (this.key_fn)(&value);
+                    let changed = this.last_key.as_ref() != Some(&key);
+                    *this.last_key = Some(key);
+                    *this.last_value = Some(value.clone());
+
+                    if changed {
+                        this.delay.as_mut().reset(Instant::now() + refresh);
+                        return Poll::Ready(Some(value));
+                    }
+                }
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => break,
+            }
+        }
+
+        // 值没有变化，但距离上次发出已超过refresh
+        if this.last_value.is_some() && this.delay.poll_unpin(cx).is_ready() {
+            this.delay.as_mut().reset(Instant::now() + refresh);
+            return Poll::Ready(this.last_value.clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<S: Stream, KeyFn, K> DistinctUntilChangedWithRefresh<S, KeyFn, K> {
+    pub fn new(stream: S, refresh: Duration, key_fn: KeyFn) -> Self {
+        Self {
+            refresh,
+            key_fn,
+            stream,
+            last_key: None,
+            last_value: None,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+struct EwmaState {
+    half_life: Duration,
+    smoothed: Option<f64>,
+    last_time: Option<Instant>,
+    last_sample: f64,
+}
+
+impl EwmaState {
+    fn new(half_life: Duration) -> Self {
+        Self {
+            half_life,
+            smoothed: None,
+            last_time: None,
+            last_sample: 0.0,
+        }
+    }
+
+    // 按距离上一个样本的实际时间计算权重
+    fn update(&mut self, now: Instant, sample: f64) -> f64 {
+        let smoothed = match (self.smoothed, self.last_time) {
+            (Some(smoothed), Some(last_time)) => {
+                let elapsed = (now - last_time).as_secs_f64();
+                let half_life = self.half_life.as_secs_f64();
+                let alpha = if half_life > 0.0 {
+                    1.0 - 0.5f64.powf(elapsed / half_life)
+                } else {
+                    1.0
+                };
+                smoothed + alpha * (sample - smoothed)
+            }
+            _ => sample,
+        };
+
+        self.smoothed = Some(smoothed);
+        self.last_time = Some(now);
+        self.last_sample = sample;
+        smoothed
+    }
+
+    // 没有新样本时，认为信号保持在最后一个样本的值
+    fn decay(&mut self, now: Instant) -> f64 {
+        self.update(now, self.last_sample)
+    }
+}
+
+#[pin_project]
+pub struct Ewma<S, F> {
+    #[pin]
+    stream: S,
+    value_fn: F,
+    state: EwmaState,
+}
+
+impl<S: Stream, F> Stream for Ewma<S, F>
+where
+    F: FnMut(&S::Item) -> f64,
+{
+    type Item = (S::Item, f64);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                let sample = (this.value_fn)(&value);
+                let smoothed = this.state.update(Instant::now(), sample);
+                Poll::Ready(Some((value, smoothed)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S, F> Ewma<S, F> {
+    pub fn new(stream: S, half_life: Duration, value_fn: F) -> Self {
+        Self {
+            stream,
+            value_fn,
+            state: EwmaState::new(half_life),
+        }
+    }
+
+    /// 超过`period`没有新样本时，用最后一个值重新输出衰减后的平滑值
+    pub fn with_tick(self, period: Duration) -> EwmaTick<S, F>
+    where
+        S: Stream,
+    {
+        EwmaTick {
+            stream: self.stream,
+            value_fn: self.value_fn,
+            state: self.state,
+            period,
+            last_value: None,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+}
+
+#[pin_project]
+pub struct EwmaTick<S: Stream, F> {
+    #[pin]
+    stream: S,
+    value_fn: F,
+    state: EwmaState,
+    period: Duration,
+    last_value: Option<S::Item>,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream, F> Stream for EwmaTick<S, F>
+where
+    S::Item: Clone,
+    F: FnMut(&S::Item) -> f64,
+{
+    type Item = (S::Item, f64);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let period = self.period;
+        let this = self.project();
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                let now = Instant::now();
+                let sample = (this.value_fn)(&value);
+                let smoothed = this.state.update(now, sample);
+                this.delay.as_mut().reset(now + period);
+                *this.last_value = Some(value.clone());
+                Poll::Ready(Some((value, smoothed)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => {
+                let Some(last_value) = this.last_value else {
+                    return Poll::Pending;
+                };
+
+                if this.delay.poll_unpin(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                // 从当前时间算起，停顿之后不会连续补发衰减的tick
+                let now = Instant::now();
+                this.delay.as_mut().reset(now + period);
+                let smoothed = this.state.decay(now);
+                Poll::Ready(Some((last_value.clone(), smoothed)))
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::future::Either;
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Idle {
+    /// 最后一个值到达的时间（没有值时为开始poll的时间）
+    pub since: Instant,
+}
+
+#[pin_project]
+pub struct Heartbeat<S> {
+    #[pin]
+    stream: S,
+    idle_after: Duration,
+    interval: Duration,
+    since: Option<Instant>,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream> Stream for Heartbeat<S> {
+    type Item = Either<S::Item, Idle>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let idle_after = self.idle_after;
+        let interval = self.interval;
+        let this = self.project();
+        let mut delay = this.delay.as_mut();
+
+        let since = match this.since {
+            Some(since) => *since,
+            None => {
+                let now = Instant::now();
+                delay.as_mut().reset(now + idle_after);
+                *this.since.insert(now)
+            }
+        };
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                let now = Instant::now();
+                *this.since = Some(now);
+                delay.reset(now + idle_after);
+                Poll::Ready(Some(Either::Left(value)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => {
+                if delay.poll_unpin(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                // 持续空闲时按interval重复，从当前时间算起，不补发错过的tick
+                delay.reset(Instant::now() + interval);
+                Poll::Ready(Some(Either::Right(Idle { since })))
+            }
+        }
+    }
+}
+
+impl<S> Heartbeat<S> {
+    pub fn new(stream: S, idle_after: Duration) -> Self {
+        Self {
+            stream,
+            idle_after,
+            interval: idle_after,
+            since: None,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+
+    /// 第一次`Idle`之后重复的间隔，默认与`idle_after`相同
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
@@ -0,0 +1,153 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 把时间对齐到系统时钟上`period`的整数倍
+#[derive(Debug, Clone)]
+pub(crate) struct Alignment {
+    period: Duration,
+    last_boundary: Option<SystemTime>,
+}
+
+impl Alignment {
+    pub(crate) fn new(period: Duration) -> Self {
+        Self {
+            period,
+            last_boundary: None,
+        }
+    }
+
+    /// 返回下一个边界，以及对应的`Instant`
+    ///
+    /// 每次都根据当前系统时间重新计算，误差不会累积；
+    /// 计时器提前触发时也不会重复同一个边界
+    pub(crate) fn next_boundary(&mut self) -> (SystemTime, Instant) {
+        let now = Instant::now();
+        let system_now = SystemTime::now();
+        let boundary = self.boundary_after(system_now);
+
+        let until = boundary.duration_since(system_now).unwrap_or_default();
+        (boundary, now + until)
+    }
+
+    fn boundary_after(&mut self, system_now: SystemTime) -> SystemTime {
+        let since_epoch = system_now.duration_since(UNIX_EPOCH).unwrap_or_default();
+
+        let period = self.period.as_nanos().max(1);
+        let next = (since_epoch.as_nanos() / period + 1) * period;
+        let mut boundary = UNIX_EPOCH + nanos(next);
+
+        if let Some(last_boundary) = self.last_boundary {
+            if boundary <= last_boundary {
+                boundary = last_boundary + self.period;
+            }
+        }
+        self.last_boundary = Some(boundary);
+        boundary
+    }
+}
+
+fn nanos(nanos: u128) -> Duration {
+    let secs = (nanos / 1_000_000_000) as u64;
+    let subsec = (nanos % 1_000_000_000) as u32;
+    Duration::new(secs, subsec)
+}
+
+pub fn interval_aligned(period: Duration) -> IntervalAligned {
+    IntervalAligned::new(period)
+}
+
+pub struct IntervalAligned {
+    alignment: Alignment,
+    next_boundary: Option<SystemTime>,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl Stream for IntervalAligned {
+    type Item = SystemTime;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        let boundary = match this.next_boundary {
+            Some(boundary) => boundary,
+            None => {
+                let (boundary, deadline) = this.alignment.next_boundary();
+                this.delay.as_mut().reset(deadline);
+                *this.next_boundary.insert(boundary)
+            }
+        };
+
+        if this.delay.poll_unpin(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        let (next, deadline) = this.alignment.next_boundary();
+        this.next_boundary = Some(next);
+        this.delay.as_mut().reset(deadline);
+        Poll::Ready(Some(boundary))
+    }
+}
+
+impl IntervalAligned {
+    pub fn new(period: Duration) -> Self {
+        Self {
+            alignment: Alignment::new(period),
+            next_boundary: None,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(millis: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn rounds_up_to_next_multiple() {
+        let mut alignment = Alignment::new(Duration::from_secs(60));
+        assert_eq!(alignment.boundary_after(at(125_000)), at(180_000));
+    }
+
+    #[test]
+    fn boundary_is_strictly_after_now() {
+        let mut alignment = Alignment::new(Duration::from_secs(60));
+        assert_eq!(alignment.boundary_after(at(120_000)), at(180_000));
+    }
+
+    #[test]
+    fn sub_second_period() {
+        let mut alignment = Alignment::new(Duration::from_millis(250));
+        assert_eq!(alignment.boundary_after(at(1_100)), at(1_250));
+        assert_eq!(alignment.boundary_after(at(1_250)), at(1_500));
+    }
+
+    #[test]
+    fn early_fire_does_not_repeat_boundary() {
+        let mut alignment = Alignment::new(Duration::from_secs(60));
+        assert_eq!(alignment.boundary_after(at(119_000)), at(120_000));
+        // 计时器比系统时钟早触发，当前时间还没到边界
+        assert_eq!(alignment.boundary_after(at(119_999)), at(180_000));
+        assert_eq!(alignment.boundary_after(at(180_000)), at(240_000));
+    }
+
+    #[test]
+    fn late_wakeup_skips_missed_boundaries() {
+        let mut alignment = Alignment::new(Duration::from_secs(60));
+        assert_eq!(alignment.boundary_after(at(119_000)), at(120_000));
+        assert_eq!(alignment.boundary_after(at(250_000)), at(300_000));
+    }
+
+    #[test]
+    fn system_clock_before_epoch() {
+        let mut alignment = Alignment::new(Duration::from_secs(60));
+        let before_epoch = UNIX_EPOCH - Duration::from_secs(10);
+        assert_eq!(alignment.boundary_after(before_epoch), at(60_000));
+    }
+}
@@ -1,8 +1,34 @@
 use futures::Stream;
+use std::future::Future;
 use std::time::Duration;
 
+pub mod aggregate;
+pub mod buffer;
+pub mod clock;
+pub mod combine;
+pub mod conflate;
 pub mod debounce;
+pub mod dedupe;
+pub mod delay;
+pub mod distinct;
+pub mod ewma;
+pub mod heartbeat;
+pub mod interval;
+pub mod quantiles;
+pub mod rate;
+pub mod retry;
+pub mod schedule;
+pub mod switch;
 pub mod throttle;
+pub mod timeout;
+pub mod timestamp;
+#[cfg(feature = "rt-tokio")]
+pub mod watch;
+
+mod time;
+
+pub use debounce::debounce_merge;
+pub use interval::interval_aligned;
 
 pub trait Streamlet {
     fn debounce_time(self, duration: Duration) -> debounce::DebounceTime<Self>
@@ -66,6 +92,272 @@ pub trait Streamlet {
     {
         throttle::ThrottleFilter::new(self, selector)
     }
+
+    fn exhaust_map<F, Fut>(self, f: F) -> throttle::ExhaustMap<Self, F, Fut>
+    where
+        Self: Sized + Stream,
+        F: FnMut(Self::Item) -> Fut,
+        Fut: Future,
+    {
+        throttle::ExhaustMap::new(self, f)
+    }
+
+    fn rate_limit_prioritized<F>(
+        self,
+        limit: throttle::RateLimit,
+        classify: F,
+    ) -> throttle::RateLimitPrioritized<Self, F>
+    where
+        Self: Sized + Stream,
+        F: FnMut(&Self::Item) -> throttle::Priority,
+    {
+        throttle::RateLimitPrioritized::new(self, limit, classify)
+    }
+
+    fn switch_map<F, Fut>(self, f: F) -> switch::SwitchMap<Self, F, Fut>
+    where
+        Self: Sized + Stream,
+        F: FnMut(Self::Item) -> Fut,
+        Fut: Future,
+    {
+        switch::SwitchMap::new(self, f)
+    }
+
+    fn switch_map_stream<F, Inner>(self, f: F) -> switch::SwitchMapStream<Self, F, Inner>
+    where
+        Self: Sized + Stream,
+        F: FnMut(Self::Item) -> Inner,
+        Inner: Stream,
+    {
+        switch::SwitchMapStream::new(self, f)
+    }
+
+    fn timeout_each(self, duration: Duration) -> timeout::TimeoutEach<Self>
+    where
+        Self: Sized,
+    {
+        timeout::TimeoutEach::new(self, duration)
+    }
+
+    fn heartbeat(self, idle_after: Duration) -> heartbeat::Heartbeat<Self>
+    where
+        Self: Sized,
+    {
+        heartbeat::Heartbeat::new(self, idle_after)
+    }
+
+    fn delay(self, duration: Duration) -> delay::Delay<Self>
+    where
+        Self: Sized + Stream,
+    {
+        delay::Delay::new(self, duration)
+    }
+
+    fn delay_when<Selector, Fut>(self, selector: Selector) -> delay::DelayWhen<Self, Selector, Fut>
+    where
+        Self: Sized + Stream,
+        Selector: FnMut(&Self::Item) -> Fut,
+        Fut: Future,
+    {
+        delay::DelayWhen::new(self, selector)
+    }
+
+    fn distinct_until_changed_with_refresh(
+        self,
+        refresh: Duration,
+    ) -> distinct::DistinctUntilChangedWithRefresh<Self, distinct::CloneKey<Self::Item>, Self::Item>
+    where
+        Self: Sized + Stream,
+        Self::Item: Clone + PartialEq,
+    {
+        distinct::DistinctUntilChangedWithRefresh::new(self, refresh, Clone::clone)
+    }
+
+    fn distinct_until_changed_with_refresh_by<KeyFn, K>(
+        self,
+        refresh: Duration,
+        key_fn: KeyFn,
+    ) -> distinct::DistinctUntilChangedWithRefresh<Self, KeyFn, K>
+    where
+        Self: Sized + Stream,
+        KeyFn: FnMut(&Self::Item) -> K,
+        K: PartialEq,
+    {
+        distinct::DistinctUntilChangedWithRefresh::new(self, refresh, key_fn)
+    }
+
+    fn dedupe_within<KeyFn, K>(
+        self,
+        ttl: Duration,
+        key_fn: KeyFn,
+    ) -> dedupe::DedupeWithin<Self, KeyFn, K>
+    where
+        Self: Sized + Stream,
+        KeyFn: FnMut(&Self::Item) -> K,
+    {
+        dedupe::DedupeWithin::new(self, ttl, key_fn)
+    }
+
+    #[cfg(feature = "rt-tokio")]
+    fn into_watch(
+        self,
+        initial: Self::Item,
+    ) -> (tokio::sync::watch::Receiver<Self::Item>, watch::WatchGuard)
+    where
+        Self: Sized + Stream + Send + 'static,
+        Self::Item: Send + Sync + 'static,
+    {
+        watch::into_watch(self, initial)
+    }
+
+    fn rate_meter(self, window: Duration) -> rate::RateMeter<Self, rate::NoWeight<Self::Item>>
+    where
+        Self: Sized + Stream,
+    {
+        rate::RateMeter::unweighted(self, window)
+    }
+
+    fn rate_meter_weighted<W>(self, window: Duration, weight_fn: W) -> rate::RateMeter<Self, W>
+    where
+        Self: Sized + Stream,
+        W: FnMut(&Self::Item) -> u64,
+    {
+        rate::RateMeter::new(self, window, weight_fn)
+    }
+
+    fn timestamp(self) -> timestamp::Timestamp<Self>
+    where
+        Self: Sized,
+    {
+        timestamp::Timestamp::new(self)
+    }
+
+    fn time_interval(self) -> timestamp::TimeInterval<Self>
+    where
+        Self: Sized,
+    {
+        timestamp::TimeInterval::new(self)
+    }
+
+    fn aggregate_time<A>(self, period: Duration, aggregator: A) -> aggregate::AggregateTime<Self, A>
+    where
+        Self: Sized + Stream,
+        A: aggregate::Aggregator<Self::Item>,
+    {
+        aggregate::AggregateTime::new(self, period, aggregator)
+    }
+
+    fn ewma<F>(self, half_life: Duration, value_fn: F) -> ewma::Ewma<Self, F>
+    where
+        Self: Sized + Stream,
+        F: FnMut(&Self::Item) -> f64,
+    {
+        ewma::Ewma::new(self, half_life, value_fn)
+    }
+
+    fn quantiles_time<F>(
+        self,
+        period: Duration,
+        quantiles: &[f64],
+        value_fn: F,
+    ) -> aggregate::AggregateTime<Self, quantiles::Quantiles<F>>
+    where
+        Self: Sized + Stream,
+        F: FnMut(&Self::Item) -> f64,
+    {
+        aggregate::AggregateTime::new(self, period, quantiles::Quantiles::new(quantiles, value_fn))
+    }
+
+    fn with_latest_from<O>(self, other: O) -> combine::WithLatestFrom<Self, O>
+    where
+        Self: Sized + Stream,
+        O: Stream,
+        O::Item: Clone,
+    {
+        combine::WithLatestFrom::new(self, other)
+    }
+
+    fn combine_latest<B>(self, other: B) -> combine::CombineLatest<Self, B>
+    where
+        Self: Sized + Stream,
+        B: Stream,
+    {
+        combine::CombineLatest::new(self, other)
+    }
+
+    fn combine_latest_debounced<B>(
+        self,
+        other: B,
+        duration: Duration,
+    ) -> combine::CombineLatestDebounced<Self, B>
+    where
+        Self: Sized + Stream,
+        B: Stream,
+    {
+        combine::CombineLatestDebounced::new(self, other, duration)
+    }
+
+    fn conflate(self) -> conflate::Conflate<Self, conflate::KeepLatest<Self::Item>>
+    where
+        Self: Sized + Stream,
+    {
+        conflate::Conflate::latest(self)
+    }
+
+    fn conflate_with<F>(self, merge_fn: F) -> conflate::Conflate<Self, F>
+    where
+        Self: Sized + Stream,
+        F: FnMut(Self::Item, Self::Item) -> Self::Item,
+    {
+        conflate::Conflate::new(self, merge_fn)
+    }
+
+    fn coalesce_by_key<KeyFn, K>(
+        self,
+        period: Duration,
+        key_fn: KeyFn,
+    ) -> conflate::CoalesceByKey<Self, KeyFn, K>
+    where
+        Self: Sized + Stream,
+        KeyFn: FnMut(&Self::Item) -> K,
+    {
+        conflate::CoalesceByKey::new(self, period, key_fn)
+    }
+
+    fn buffer_bounded(
+        self,
+        capacity: usize,
+        policy: buffer::OverflowPolicy,
+    ) -> buffer::BufferBounded<Self, buffer::IgnoreDropped<Self::Item>>
+    where
+        Self: Sized + Stream,
+    {
+        buffer::BufferBounded::new(self, capacity, policy)
+    }
+
+    fn schedule_by<D>(
+        self,
+        deadline_fn: D,
+    ) -> schedule::ScheduleBy<Self, D, schedule::NoKey<Self::Item>, ()>
+    where
+        Self: Sized + Stream,
+        D: FnMut(&Self::Item) -> clock::Instant,
+    {
+        schedule::ScheduleBy::new(self, deadline_fn, |_| ())
+    }
+
+    fn schedule_by_key<D, KeyFn, K>(
+        self,
+        deadline_fn: D,
+        key_fn: KeyFn,
+    ) -> schedule::ScheduleBy<Self, D, KeyFn, K>
+    where
+        Self: Sized + Stream,
+        D: FnMut(&Self::Item) -> clock::Instant,
+        KeyFn: FnMut(&Self::Item) -> K,
+    {
+        schedule::ScheduleBy::new(self, deadline_fn, key_fn)
+    }
 }
 
 impl<S: Stream> Streamlet for S {}
@@ -0,0 +1,275 @@
+use crate::aggregate::Aggregator;
+use std::collections::BTreeMap;
+
+// 相对误差1%
+const RELATIVE_ACCURACY: f64 = 0.01;
+// 每一侧最多保留的桶数，超出后合并最小的桶
+const MAX_BINS: usize = 2048;
+const MIN_INDEXABLE: f64 = 1e-9;
+
+/// DDSketch，内存占用与样本数量无关
+#[derive(Debug, Clone)]
+struct Sketch {
+    gamma: f64,
+    ln_gamma: f64,
+    positive: BTreeMap<i32, u64>,
+    negative: BTreeMap<i32, u64>,
+    zero: u64,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Sketch {
+    fn new() -> Self {
+        let gamma = (1.0 + RELATIVE_ACCURACY) / (1.0 - RELATIVE_ACCURACY);
+        Self {
+            gamma,
+            ln_gamma: gamma.ln(),
+            positive: BTreeMap::new(),
+            negative: BTreeMap::new(),
+            zero: 0,
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    fn index(&self, value: f64) -> i32 {
+        (value.ln() / self.ln_gamma).ceil() as i32
+    }
+
+    fn value(&self, index: i32) -> f64 {
+        2.0 * self.gamma.powi(index) / (self.gamma + 1.0)
+    }
+
+    fn insert(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+
+        if value > MIN_INDEXABLE {
+            let index = self.index(value);
+            *self.positive.entry(index).or_default() += 1;
+            collapse(&mut self.positive);
+        } else if value < -MIN_INDEXABLE {
+            let index = self.index(-value);
+            *self.negative.entry(index).or_default() += 1;
+            collapse(&mut self.negative);
+        } else {
+            self.zero += 1;
+        }
+
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn quantile(&self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let rank = (q.clamp(0.0, 1.0) * (self.count - 1) as f64) as u64;
+        let mut seen = 0;
+
+        for (&index, &count) in self.negative.iter().rev() {
+            seen += count;
+            if seen > rank {
+                return Some((-self.value(index)).clamp(self.min, self.max));
+            }
+        }
+
+        seen += self.zero;
+        if seen > rank {
+            return Some(0.0);
+        }
+
+        for (&index, &count) in self.positive.iter() {
+            seen += count;
+            if seen > rank {
+                return Some(self.value(index).clamp(self.min, self.max));
+            }
+        }
+
+        Some(self.max)
+    }
+}
+
+fn collapse(bins: &mut BTreeMap<i32, u64>) {
+    while bins.len() > MAX_BINS {
+        if let (Some((_, lowest)), Some(mut next)) = (bins.pop_first(), bins.first_entry()) {
+            *next.get_mut() += lowest;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantileSummary {
+    pub count: u64,
+    pub sum: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// `(q, value)`，窗口内没有值时`value`为`None`
+    pub quantiles: Vec<(f64, Option<f64>)>,
+}
+
+impl QuantileSummary {
+    pub fn get(&self, q: f64) -> Option<f64> {
+        self.quantiles
+            .iter()
+            .find(|(quantile, _)| *quantile == q)
+            .and_then(|(_, value)| *value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Quantiles<F> {
+    quantiles: Vec<f64>,
+    value_fn: F,
+    sketch: Sketch,
+}
+
+impl<F> Quantiles<F> {
+    pub fn new(quantiles: &[f64], value_fn: F) -> Self {
+        Self {
+            quantiles: quantiles.to_vec(),
+            value_fn,
+            sketch: Sketch::new(),
+        }
+    }
+}
+
+impl<T, F> Aggregator<T> for Quantiles<F>
+where
+    F: FnMut(&T) -> f64,
+{
+    type Output = QuantileSummary;
+
+    fn push(&mut self, item: T) {
+        let value = (self.value_fn)(&item);
+        self.sketch.insert(value);
+    }
+
+    fn take(&mut self) -> Self::Output {
+        let sketch = std::mem::replace(&mut self.sketch, Sketch::new());
+        let non_empty = sketch.count > 0;
+
+        QuantileSummary {
+            count: sketch.count,
+            sum: sketch.sum,
+            min: non_empty.then_some(sketch.min),
+            max: non_empty.then_some(sketch.max),
+            quantiles: self
+                .quantiles
+                .iter()
+                .map(|&q| (q, sketch.quantile(q)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(estimate: f64, exact: f64) {
+        let error = (estimate - exact).abs();
+        assert!(
+            error <= RELATIVE_ACCURACY * exact.abs() + 1e-12,
+            "estimate {estimate} is not within 1% of {exact}"
+        );
+    }
+
+    fn exact_quantile(sorted: &[f64], q: f64) -> f64 {
+        sorted[(q * (sorted.len() - 1) as f64) as usize]
+    }
+
+    fn check_quantiles(values: &[f64]) {
+        let mut sketch = Sketch::new();
+        for &value in values {
+            sketch.insert(value);
+        }
+
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        for q in [0.0, 0.01, 0.25, 0.5, 0.75, 0.9, 0.99, 1.0] {
+            assert_close(sketch.quantile(q).unwrap(), exact_quantile(&sorted, q));
+        }
+    }
+
+    #[test]
+    fn bucket_value_within_relative_error() {
+        let sketch = Sketch::new();
+        let mut value = 1e-6;
+        while value < 1e12 {
+            assert_close(sketch.value(sketch.index(value)), value);
+            value *= 1.37;
+        }
+    }
+
+    #[test]
+    fn positive_values() {
+        let values: Vec<f64> = (1..=1000).map(|i| i as f64).collect();
+        check_quantiles(&values);
+    }
+
+    #[test]
+    fn negative_values() {
+        let values: Vec<f64> = (1..=1000).map(|i| -(i as f64) * 0.5).collect();
+        check_quantiles(&values);
+    }
+
+    #[test]
+    fn mixed_values_with_zeros() {
+        let values: Vec<f64> = (-500..=500).map(|i| (i / 3) as f64 * 1.5).collect();
+        check_quantiles(&values);
+    }
+
+    #[test]
+    fn only_zeros() {
+        let mut sketch = Sketch::new();
+        for _ in 0..10 {
+            sketch.insert(0.0);
+        }
+        assert_eq!(sketch.zero, 10);
+        assert_eq!(sketch.quantile(0.5), Some(0.0));
+        assert_eq!(sketch.quantile(1.0), Some(0.0));
+    }
+
+    #[test]
+    fn nan_is_ignored() {
+        let mut sketch = Sketch::new();
+        sketch.insert(f64::NAN);
+        assert_eq!(sketch.count, 0);
+        assert_eq!(sketch.quantile(0.5), None);
+
+        for i in 1..=100 {
+            sketch.insert(i as f64);
+            sketch.insert(f64::NAN);
+        }
+        assert_eq!(sketch.count, 100);
+        assert_eq!(sketch.sum, 5050.0);
+        assert_close(sketch.quantile(0.5).unwrap(), 50.0);
+    }
+
+    #[test]
+    fn collapse_keeps_upper_quantiles() {
+        let mut sketch = Sketch::new();
+        let values: Vec<f64> = (0..2 * MAX_BINS)
+            .map(|i| sketch.gamma.powi(i as i32))
+            .collect();
+        for &value in &values {
+            sketch.insert(value);
+        }
+
+        assert_eq!(sketch.positive.len(), MAX_BINS);
+        assert_eq!(sketch.positive.values().sum::<u64>(), values.len() as u64);
+        for q in [0.5, 0.9, 0.99, 1.0] {
+            assert_close(sketch.quantile(q).unwrap(), exact_quantile(&values, q));
+        }
+    }
+}
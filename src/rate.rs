@@ -0,0 +1,239 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+// 每个窗口切分成的桶数，内存占用与流量无关
+const BUCKETS: u32 = 16;
+
+pub type NoWeight<T> = fn(&T) -> u64;
+
+#[derive(Debug, Copy, Clone)]
+pub struct RateSample {
+    pub at: Instant,
+    pub items_per_sec: f64,
+    /// 只有通过`rate_meter_weighted`创建时才有值
+    pub bytes_per_sec: Option<f64>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    start: Instant,
+    items: u64,
+    weight: u64,
+}
+
+#[derive(Debug)]
+struct RateWindow {
+    window: Duration,
+    weighted: bool,
+    started: Option<Instant>,
+    terminated: bool,
+    buckets: VecDeque<Bucket>,
+}
+
+impl RateWindow {
+    fn bucket_width(&self) -> Duration {
+        self.window / BUCKETS
+    }
+
+    fn evict(&mut self, now: Instant) {
+        while let Some(bucket) = self.buckets.front() {
+            if bucket.start + self.window > now {
+                break;
+            }
+            self.buckets.pop_front();
+        }
+    }
+
+    fn record(&mut self, now: Instant, weight: u64) {
+        self.evict(now);
+        let width = self.bucket_width();
+        match self.buckets.back_mut() {
+            Some(bucket) if bucket.start + width > now => {
+                bucket.items += 1;
+                bucket.weight += weight;
+            }
+            _ => self.buckets.push_back(Bucket {
+                start: now,
+                items: 1,
+                weight,
+            }),
+        }
+    }
+
+    fn sample(&mut self, now: Instant) -> RateSample {
+        self.evict(now);
+
+        // 还没跑满一个窗口时按实际经过的时间计算
+        let elapsed = self
+            .started
+            .map(|started| now - started)
+            .unwrap_or_default()
+            .min(self.window)
+            .as_secs_f64();
+
+        let (items, weight) = self.buckets.iter().fold((0, 0), |(items, weight), bucket| {
+            (items + bucket.items, weight + bucket.weight)
+        });
+
+        let per_sec = |count: u64| {
+            if elapsed > 0.0 {
+                count as f64 / elapsed
+            } else {
+                0.0
+            }
+        };
+
+        RateSample {
+            at: now,
+            items_per_sec: per_sec(items),
+            bytes_per_sec: self.weighted.then(|| per_sec(weight)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateHandle {
+    window: Arc<Mutex<RateWindow>>,
+}
+
+impl RateHandle {
+    fn lock(&self) -> MutexGuard<'_, RateWindow> {
+        self.window.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn sample(&self) -> RateSample {
+        self.lock().sample(Instant::now())
+    }
+
+    pub fn items_per_sec(&self) -> f64 {
+        self.sample().items_per_sec
+    }
+
+    pub fn bytes_per_sec(&self) -> Option<f64> {
+        self.sample().bytes_per_sec
+    }
+
+    /// 每隔`period`产出一个`RateSample`，被测量的stream结束后也随之结束
+    pub fn samples(&self, period: Duration) -> RateSamples {
+        RateSamples {
+            handle: self.clone(),
+            period,
+            delay: Box::pin(sleep(period)),
+            terminated: false,
+        }
+    }
+}
+
+#[pin_project]
+pub struct RateMeter<S, W> {
+    #[pin]
+    stream: S,
+    weight_fn: W,
+    handle: RateHandle,
+}
+
+impl<S: Stream, W> Stream for RateMeter<S, W>
+where
+    W: FnMut(&S::Item) -> u64,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        {
+            let mut window = this.handle.lock();
+            if window.started.is_none() {
+                window.started = Some(Instant::now());
+            }
+        }
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                let weight = (this.weight_fn)(&value);
+                this.handle.lock().record(Instant::now(), weight);
+                Poll::Ready(Some(value))
+            }
+            Poll::Ready(None) => {
+                this.handle.lock().terminated = true;
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S, W> RateMeter<S, W> {
+    pub fn new(stream: S, window: Duration, weight_fn: W) -> Self {
+        Self::with_weighted(stream, window, weight_fn, true)
+    }
+
+    fn with_weighted(stream: S, window: Duration, weight_fn: W, weighted: bool) -> Self {
+        let window = RateWindow {
+            window,
+            weighted,
+            started: None,
+            terminated: false,
+            buckets: VecDeque::new(),
+        };
+
+        Self {
+            stream,
+            weight_fn,
+            handle: RateHandle {
+                window: Arc::new(Mutex::new(window)),
+            },
+        }
+    }
+
+    pub fn handle(&self) -> RateHandle {
+        self.handle.clone()
+    }
+}
+
+impl<S: Stream> RateMeter<S, NoWeight<S::Item>> {
+    pub fn unweighted(stream: S, window: Duration) -> Self {
+        Self::with_weighted(stream, window, |_| 0, false)
+    }
+}
+
+pub struct RateSamples {
+    handle: RateHandle,
+    period: Duration,
+    delay: Pin<Box<Sleep>>,
+    terminated: bool,
+}
+
+impl Stream for RateSamples {
+    type Item = RateSample;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.terminated {
+            return Poll::Ready(None);
+        }
+
+        if self.delay.poll_unpin(cx).is_pending() {
+            return Poll::Pending;
+        }
+
+        // 从当前时间算起，消费者停顿后不会连续产出相同的样本
+        let now = Instant::now();
+        let next = now + self.period;
+        self.delay.as_mut().reset(next);
+
+        let mut window = self.handle.lock();
+        let sample = window.sample(now);
+        let terminated = window.terminated;
+        drop(window);
+
+        // 被测量的stream结束后产出最后一个样本
+        self.terminated = terminated;
+        Poll::Ready(Some(sample))
+    }
+}
@@ -0,0 +1,309 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream, TryStream};
+use pin_project::pin_project;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+// 等待时间溢出`Instant`时的截止时间，与tokio一样取大约30年后
+const FAR_FUTURE: Duration = Duration::from_secs(86400 * 365 * 30);
+
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    /// 第一次重试前的等待时间
+    pub initial: Duration,
+    /// 等待时间的上限
+    pub max: Duration,
+    /// 每次失败后等待时间的倍数
+    pub multiplier: f64,
+    /// 随机减少等待时间的比例，取值`0.0..=1.0`
+    pub jitter: f64,
+    /// 连续失败多少次后放弃（包括第一次创建），`None`表示不限制
+    ///
+    /// 重新创建的stream产出值后，失败计数会重置
+    pub max_attempts: Option<usize>,
+    /// 从第一次失败开始，持续失败多久后放弃，`None`表示不限制
+    pub max_elapsed: Option<Duration>,
+    /// stream没有产出值、但也没有失败地运行了多久后，重置失败计数
+    pub reset_after: Option<Duration>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.1,
+            max_attempts: None,
+            max_elapsed: None,
+            reset_after: None,
+        }
+    }
+}
+
+impl Backoff {
+    fn delay(&self, attempts: usize, random: f64) -> Duration {
+        let exp = attempts.saturating_sub(1).min(i32::MAX as usize) as i32;
+        let delay = self.initial.as_secs_f64() * self.multiplier.powi(exp);
+        let delay = delay.min(self.max.as_secs_f64());
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        // `max`可能是`Duration::MAX`，`jitter`也可能是NaN，转换失败时退回到上限
+        Duration::try_from_secs_f64(delay * (1.0 - jitter * random)).unwrap_or(self.max)
+    }
+}
+
+pub fn retry_stream<F, S>(factory: F, backoff: Backoff) -> RetryStream<F, S>
+where
+    F: FnMut() -> S,
+    S: TryStream,
+{
+    RetryStream::new(factory, backoff)
+}
+
+#[pin_project]
+pub struct RetryStream<F, S> {
+    factory: F,
+    backoff: Backoff,
+    #[pin]
+    stream: Option<S>,
+    started_at: Instant,
+    failing_since: Option<Instant>,
+    attempts: usize,
+    terminated: bool,
+    seed: u64,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<F, S> Stream for RetryStream<F, S>
+where
+    F: FnMut() -> S,
+    S: TryStream,
+{
+    type Item = Result<S::Ok, S::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.terminated {
+                break Poll::Ready(None);
+            }
+
+            let Some(pin_stream) = this.stream.as_mut().as_pin_mut() else {
+                // 等待backoff结束后重新创建stream
+                if this.delay.poll_unpin(cx).is_pending() {
+                    break Poll::Pending;
+                }
+                this.stream.set(Some((this.factory)()));
+                *this.started_at = Instant::now();
+                continue;
+            };
+
+            match pin_stream.try_poll_next(cx) {
+                Poll::Ready(Some(Ok(value))) => {
+                    // stream恢复正常，不再算作连续失败
+                    *this.attempts = 0;
+                    *this.failing_since = None;
+                    break Poll::Ready(Some(Ok(value)));
+                }
+                Poll::Ready(Some(Err(err))) => {
+                    this.stream.set(None);
+                    let now = Instant::now();
+
+                    if let Some(reset_after) = this.backoff.reset_after {
+                        if now - *this.started_at >= reset_after {
+                            *this.attempts = 0;
+                            *this.failing_since = None;
+                        }
+                    }
+
+                    *this.attempts += 1;
+                    let failing_since = *this.failing_since.get_or_insert(now);
+
+                    let exhausted = this
+                        .backoff
+                        .max_attempts
+                        .is_some_and(|max| *this.attempts >= max)
+                        || this
+                            .backoff
+                            .max_elapsed
+                            .is_some_and(|max| now - failing_since >= max);
+                    if exhausted {
+                        *this.terminated = true;
+                        break Poll::Ready(Some(Err(err)));
+                    }
+
+                    let random = next_random(this.seed);
+                    let delay = this.backoff.delay(*this.attempts, random);
+                    let deadline = now.checked_add(delay).unwrap_or(now + FAR_FUTURE);
+                    this.delay.as_mut().reset(deadline);
+                }
+                Poll::Ready(None) => {
+                    *this.terminated = true;
+                    break Poll::Ready(None);
+                }
+                Poll::Pending => break Poll::Pending,
+            }
+        }
+    }
+}
+
+impl<F, S> RetryStream<F, S>
+where
+    F: FnMut() -> S,
+{
+    pub fn new(mut factory: F, backoff: Backoff) -> Self {
+        let stream = factory();
+        Self {
+            factory,
+            backoff,
+            stream: Some(stream),
+            started_at: Instant::now(),
+            failing_since: None,
+            attempts: 0,
+            terminated: false,
+            seed: RandomState::new().build_hasher().finish() | 1,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+}
+
+// xorshift，只用于jitter，返回`0.0..1.0`
+fn next_random(seed: &mut u64) -> f64 {
+    let mut x = *seed;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    *seed = x;
+    (x >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::block_on;
+    use futures::{stream, StreamExt};
+
+    fn backoff() -> Backoff {
+        Backoff {
+            initial: Duration::from_millis(1),
+            jitter: 0.0,
+            ..Default::default()
+        }
+    }
+
+    fn failing() -> stream::BoxStream<'static, Result<u32, &'static str>> {
+        stream::iter([Err("e1")]).boxed()
+    }
+
+    #[test]
+    fn delay_without_cap() {
+        let backoff = Backoff {
+            max: Duration::MAX,
+            jitter: 0.0,
+            ..backoff()
+        };
+        assert_eq!(backoff.delay(1, 0.0), Duration::from_millis(1));
+        assert_eq!(backoff.delay(3, 0.0), Duration::from_millis(4));
+        assert_eq!(backoff.delay(usize::MAX, 0.0), Duration::MAX);
+    }
+
+    #[test]
+    fn delay_with_nan_jitter() {
+        let backoff = Backoff {
+            jitter: f64::NAN,
+            ..backoff()
+        };
+        assert_eq!(backoff.delay(1, 0.5), backoff.max);
+    }
+
+    #[test]
+    fn retry_without_cap_does_not_overflow() {
+        block_on(async {
+            let mut retry = retry_stream(
+                failing,
+                Backoff {
+                    initial: Duration::MAX,
+                    max: Duration::MAX,
+                    ..backoff()
+                },
+            );
+
+            // 等待时间溢出时退回到很久以后，而不是panic
+            assert!(futures::poll!(retry.next()).is_pending());
+        });
+    }
+
+    #[test]
+    fn ok_resets_max_attempts() {
+        block_on(async {
+            let mut created = 0;
+            let retry = retry_stream(
+                move || {
+                    created += 1;
+                    match created {
+                        1 => failing(),
+                        2 => stream::iter([Ok(1), Err("e2")]).boxed(),
+                        _ => stream::iter([Ok(2)]).boxed(),
+                    }
+                },
+                Backoff {
+                    max_attempts: Some(2),
+                    ..backoff()
+                },
+            );
+
+            let items: Vec<_> = retry.collect().await;
+            assert_eq!(items, vec![Ok(1), Ok(2)]);
+        });
+    }
+
+    #[test]
+    fn ok_resets_max_elapsed() {
+        block_on(async {
+            let mut created = 0;
+            let retry = retry_stream(
+                move || {
+                    created += 1;
+                    match created {
+                        1 => failing(),
+                        // 健康运行的时间超过了max_elapsed之后才再次失败
+                        2 => stream::iter([Ok(1)])
+                            .chain(stream::once(async {
+                                sleep(Duration::from_millis(50)).await;
+                                Err("e2")
+                            }))
+                            .boxed(),
+                        _ => stream::iter([Ok(2)]).boxed(),
+                    }
+                },
+                Backoff {
+                    max_elapsed: Some(Duration::from_millis(20)),
+                    ..backoff()
+                },
+            );
+
+            let items: Vec<_> = retry.collect().await;
+            assert_eq!(items, vec![Ok(1), Ok(2)]);
+        });
+    }
+
+    #[test]
+    fn continuous_failures_give_up() {
+        block_on(async {
+            let retry = retry_stream(
+                failing,
+                Backoff {
+                    max_attempts: Some(3),
+                    ..backoff()
+                },
+            );
+
+            let items: Vec<_> = retry.collect().await;
+            assert_eq!(items, vec![Err("e1")]);
+        });
+    }
+}
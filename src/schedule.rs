@@ -0,0 +1,165 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::collections::BTreeMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+pub type NoKey<T> = fn(&T);
+
+#[derive(Debug)]
+struct TimerQueue<K, T> {
+    // 按(到期时间, 到达顺序)排序
+    entries: BTreeMap<(Instant, u64), (K, T)>,
+    next_seq: u64,
+    // 等待下一个到期时间的task，取消后需要唤醒它重新计算
+    waker: Option<Waker>,
+}
+
+impl<K, T> TimerQueue<K, T> {
+    fn push(&mut self, deadline: Instant, key: K, value: T) {
+        self.entries.insert((deadline, self.next_seq), (key, value));
+        self.next_seq += 1;
+    }
+}
+
+#[derive(Debug)]
+pub struct ScheduleHandle<K, T> {
+    queue: Arc<Mutex<TimerQueue<K, T>>>,
+}
+
+impl<K, T> Clone for ScheduleHandle<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            queue: self.queue.clone(),
+        }
+    }
+}
+
+impl<K, T> ScheduleHandle<K, T> {
+    fn lock(&self) -> MutexGuard<'_, TimerQueue<K, T>> {
+        self.queue.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// 取消所有还没到期的、key为`key`的值，返回取消的数量
+    pub fn cancel(&self, key: &K) -> usize
+    where
+        K: PartialEq,
+    {
+        let mut queue = self.lock();
+        let before = queue.entries.len();
+        queue.entries.retain(|_, (entry_key, _)| entry_key != key);
+        let cancelled = before - queue.entries.len();
+        let waker = if cancelled > 0 {
+            queue.waker.take()
+        } else {
+            None
+        };
+        drop(queue);
+
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+        cancelled
+    }
+
+    /// 还没到期的值的数量
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[pin_project]
+pub struct ScheduleBy<S: Stream, D, KeyFn, K> {
+    deadline_fn: D,
+    key_fn: KeyFn,
+    #[pin]
+    stream: Option<S>,
+    handle: ScheduleHandle<K, S::Item>,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream, D, KeyFn, K> Stream for ScheduleBy<S, D, KeyFn, K>
+where
+    D: FnMut(&S::Item) -> Instant,
+    KeyFn: FnMut(&S::Item) -> K,
+{
+    type Item = S::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        // 先不加锁地取出新到的值，上游和用户的闭包里也可以调用`cancel`
+        let mut arrived = Vec::new();
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => {
+                    let deadline = (this.deadline_fn)(&value);
+                    let key = (this.key_fn)(&value);
+                    arrived.push((deadline, key, value));
+                }
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        let mut queue = this.handle.lock();
+        for (deadline, key, value) in arrived {
+            queue.push(deadline, key, value);
+        }
+        queue.waker = Some(cx.waker().clone());
+
+        let Some(first) = queue.entries.first_entry() else {
+            return if this.stream.is_none() {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            };
+        };
+
+        // 已经过期的值立即输出
+        let (deadline, _) = *first.key();
+        if deadline > Instant::now() {
+            let mut delay = this.delay.as_mut();
+            if delay.deadline() != deadline {
+                delay.as_mut().reset(deadline);
+            }
+            if delay.poll_unpin(cx).is_pending() {
+                return Poll::Pending;
+            }
+        }
+
+        let (_, value) = first.remove();
+        Poll::Ready(Some(value))
+    }
+}
+
+impl<S: Stream, D, KeyFn, K> ScheduleBy<S, D, KeyFn, K> {
+    pub fn new(stream: S, deadline_fn: D, key_fn: KeyFn) -> Self {
+        let queue = TimerQueue {
+            entries: BTreeMap::new(),
+            next_seq: 0,
+            waker: None,
+        };
+
+        Self {
+            deadline_fn,
+            key_fn,
+            stream: Some(stream),
+            handle: ScheduleHandle {
+                queue: Arc::new(Mutex::new(queue)),
+            },
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+
+    pub fn handle(&self) -> ScheduleHandle<K, S::Item> {
+        self.handle.clone()
+    }
+}
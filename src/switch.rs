@@ -0,0 +1,114 @@
+use futures::Stream;
+use pin_project::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[pin_project]
+pub struct SwitchMap<S, F, Fut> {
+    #[pin]
+    stream: Option<S>,
+    f: F,
+    #[pin]
+    in_flight: Option<Fut>,
+}
+
+impl<S: Stream, F, Fut> Stream for SwitchMap<S, F, Fut>
+where
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future,
+{
+    type Item = Fut::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        // 新值到达时丢弃执行中的future
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => this.in_flight.set(Some((this.f)(value))),
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        if let Some(in_flight) = this.in_flight.as_mut().as_pin_mut() {
+            if let Poll::Ready(output) = in_flight.poll(cx) {
+                this.in_flight.set(None);
+                return Poll::Ready(Some(output));
+            }
+        }
+
+        // stream结束且没有执行中的future
+        if this.stream.is_none() && this.in_flight.is_none() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S, F, Fut> SwitchMap<S, F, Fut> {
+    pub fn new(stream: S, f: F) -> Self {
+        Self {
+            stream: Some(stream),
+            f,
+            in_flight: None,
+        }
+    }
+}
+
+#[pin_project]
+pub struct SwitchMapStream<S, F, Inner> {
+    #[pin]
+    stream: Option<S>,
+    f: F,
+    #[pin]
+    inner: Option<Inner>,
+}
+
+impl<S: Stream, F, Inner> Stream for SwitchMapStream<S, F, Inner>
+where
+    F: FnMut(S::Item) -> Inner,
+    Inner: Stream,
+{
+    type Item = Inner::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        // 新值到达时丢弃当前的内部stream
+        while let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+            match pin_stream.poll_next(cx) {
+                Poll::Ready(Some(value)) => this.inner.set(Some((this.f)(value))),
+                Poll::Ready(None) => this.stream.set(None),
+                Poll::Pending => break,
+            }
+        }
+
+        if let Some(inner) = this.inner.as_mut().as_pin_mut() {
+            match inner.poll_next(cx) {
+                Poll::Ready(Some(value)) => return Poll::Ready(Some(value)),
+                Poll::Ready(None) => this.inner.set(None),
+                Poll::Pending => {}
+            }
+        }
+
+        // stream结束且内部stream也已结束
+        if this.stream.is_none() && this.inner.is_none() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+impl<S, F, Inner> SwitchMapStream<S, F, Inner> {
+    pub fn new(stream: S, f: F) -> Self {
+        Self {
+            stream: Some(stream),
+            f,
+            inner: None,
+        }
+    }
+}
@@ -1,32 +1,35 @@
+use crate::clock::{Clock, DefaultClock};
+use crate::time::Instant;
 use futures::{FutureExt, Stream};
 use pin_project::pin_project;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::time::Duration;
-use tokio::time::{sleep, Instant, Sleep};
 
 #[pin_project]
-pub struct ThrottleTimeFilter<S> {
+pub struct ThrottleTimeFilter<S, C: Clock = DefaultClock> {
     #[pin]
     stream: S,
     duration: Duration,
-    delay: Pin<Box<Sleep>>,
+    clock: C,
+    delay: Pin<Box<C::Sleep>>,
 }
 
-impl<S: Stream> Stream for ThrottleTimeFilter<S> {
+impl<S: Stream, C: Clock> Stream for ThrottleTimeFilter<S, C> {
     type Item = S::Item;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let duration = self.duration;
         let this = self.project();
         let stream: Pin<&mut S> = this.stream;
-        let mut delay: Pin<&mut Sleep> = this.delay.as_mut();
+        let clock: &C = this.clock;
+        let mut delay: Pin<&mut C::Sleep> = this.delay.as_mut();
 
         match stream.poll_next(cx) {
             Poll::Ready(Some(value)) => {
                 if delay.poll_unpin(cx).is_ready() {
-                    delay.reset((Instant::now() + duration).into());
+                    clock.reset(delay, clock.now() + duration);
                     Poll::Ready(Some(value))
                 } else {
                     Poll::Pending
@@ -40,43 +43,52 @@ impl<S: Stream> Stream for ThrottleTimeFilter<S> {
 
 impl<S> ThrottleTimeFilter<S> {
     pub fn new(stream: S, duration: Duration) -> Self {
+        Self::with_clock(stream, duration, DefaultClock::default())
+    }
+}
+
+impl<S, C: Clock> ThrottleTimeFilter<S, C> {
+    pub fn with_clock(stream: S, duration: Duration, clock: C) -> Self {
         Self {
             stream,
             duration,
-            delay: Box::pin(sleep(Duration::from_nanos(0))),
+            delay: Box::pin(clock.sleep_until(clock.now())),
+            clock,
         }
     }
 }
 
 #[pin_project]
-pub struct ThrottleTime<S> {
+pub struct ThrottleTime<S, C: Clock = DefaultClock> {
     #[pin]
     stream: S,
     duration: Duration,
-    last_time: Option<Instant>,
+    clock: C,
+    last_time: Option<C::Instant>,
 }
 
 #[derive(Debug, Copy, Clone)]
 pub struct Throttled<T>(pub T);
 
-impl<S: Stream> Stream for ThrottleTime<S> {
+impl<S: Stream, C: Clock> Stream for ThrottleTime<S, C> {
     type Item = Result<S::Item, Throttled<S::Item>>;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let duration = self.duration;
         let this = self.project();
         let stream: Pin<&mut S> = this.stream;
-        let last_time: &mut Option<Instant> = this.last_time;
+        let clock: &C = this.clock;
+        let last_time: &mut Option<C::Instant> = this.last_time;
 
         match stream.poll_next(cx) {
             Poll::Ready(Some(value)) => {
+                let now = clock.now();
                 if last_time
-                    .as_ref()
-                    .map(Instant::elapsed)
+                    .map(|last_time| now - last_time)
                     .map(|i| i > duration)
                     .unwrap_or(true)
                 {
-                    *last_time = Some(Instant::now());
+                    *last_time = Some(now);
                     Poll::Ready(Some(Ok(value)))
                 } else {
                     Poll::Ready(Some(Err(Throttled(value))))
@@ -90,9 +102,16 @@ impl<S: Stream> Stream for ThrottleTime<S> {
 
 impl<S> ThrottleTime<S> {
     pub fn new(stream: S, duration: Duration) -> Self {
+        Self::with_clock(stream, duration, DefaultClock::default())
+    }
+}
+
+impl<S, C: Clock> ThrottleTime<S, C> {
+    pub fn with_clock(stream: S, duration: Duration, clock: C) -> Self {
         Self {
             stream,
             duration,
+            clock,
             last_time: None,
         }
     }
@@ -211,3 +230,197 @@ impl<S, Selector, Fut> Throttle<S, Selector, Fut> {
         }
     }
 }
+
+#[pin_project]
+pub struct ExhaustMap<S, F, Fut> {
+    #[pin]
+    stream: Option<S>,
+    f: F,
+    #[pin]
+    in_flight: Option<Fut>,
+}
+
+impl<S: Stream, F, Fut> Stream for ExhaustMap<S, F, Fut>
+where
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future,
+{
+    type Item = Fut::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        loop {
+            if let Some(in_flight) = this.in_flight.as_mut().as_pin_mut() {
+                if let Poll::Ready(output) = in_flight.poll(cx) {
+                    this.in_flight.set(None);
+                    return Poll::Ready(Some(output));
+                }
+            }
+
+            if let Some(pin_stream) = this.stream.as_mut().as_pin_mut() {
+                match pin_stream.poll_next(cx) {
+                    Poll::Ready(Some(value)) => {
+                        // future执行期间到达的值直接丢弃
+                        if this.in_flight.is_none() {
+                            this.in_flight.set(Some((this.f)(value)));
+                        }
+                        continue;
+                    }
+                    Poll::Ready(None) => this.stream.set(None),
+                    Poll::Pending => {}
+                }
+            }
+
+            // stream结束且没有执行中的future
+            break if this.stream.is_none() && this.in_flight.is_none() {
+                Poll::Ready(None)
+            } else {
+                Poll::Pending
+            };
+        }
+    }
+}
+
+impl<S, F, Fut> ExhaustMap<S, F, Fut> {
+    pub fn new(stream: S, f: F) -> Self {
+        Self {
+            stream: Some(stream),
+            f,
+            in_flight: None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// 额度不足时最先被丢弃，也不能使用`RateLimit::reserved`
+    BestEffort,
+    Normal,
+    /// 总是通过
+    Critical,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct RateLimit {
+    /// 每个`per`周期最多通过的数量
+    pub count: u32,
+    pub per: Duration,
+    /// 只有`Normal`及以上才能使用的额度
+    pub reserved: u32,
+}
+
+impl RateLimit {
+    /// 默认预留四分之一的额度给`Normal`
+    pub fn new(count: u32, per: Duration) -> Self {
+        Self {
+            count,
+            per,
+            reserved: count / 4,
+        }
+    }
+}
+
+#[pin_project]
+pub struct RateLimitPrioritized<S, F> {
+    #[pin]
+    stream: S,
+    limit: RateLimit,
+    classify: F,
+    tokens: f64,
+    last_refill: Option<Instant>,
+}
+
+impl<S: Stream, F> Stream for RateLimitPrioritized<S, F>
+where
+    F: FnMut(&S::Item) -> Priority,
+{
+    type Item = Result<S::Item, (Throttled<S::Item>, Priority)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let limit = self.limit;
+        let this = self.project();
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                // 令牌桶，按经过的时间补充额度
+                let now = Instant::now();
+                let capacity = limit.count as f64;
+                if let Some(last_refill) = *this.last_refill {
+                    let rate = capacity / limit.per.as_secs_f64();
+                    let refill = (now - last_refill).as_secs_f64() * rate;
+                    *this.tokens = (*this.tokens + refill).min(capacity);
+                }
+                *this.last_refill = Some(now);
+
+                let priority = (this.classify)(&value);
+                let admitted = match priority {
+                    Priority::Critical => true,
+                    Priority::Normal => *this.tokens >= 1.0,
+                    Priority::BestEffort => *this.tokens >= 1.0 + limit.reserved as f64,
+                };
+
+                if admitted {
+                    *this.tokens = (*this.tokens - 1.0).max(0.0);
+                    Poll::Ready(Some(Ok(value)))
+                } else {
+                    Poll::Ready(Some(Err((Throttled(value), priority))))
+                }
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S, F> RateLimitPrioritized<S, F> {
+    pub fn new(stream: S, limit: RateLimit, classify: F) -> Self {
+        Self {
+            stream,
+            limit,
+            classify,
+            tokens: limit.count as f64,
+            last_refill: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use futures::channel::mpsc;
+    use futures::StreamExt;
+
+    #[test]
+    fn throttle_time_with_manual_clock() {
+        let clock = ManualClock::new();
+        let (tx, rx) = mpsc::unbounded();
+        let mut throttled = ThrottleTime::with_clock(rx, Duration::from_millis(100), clock.clone());
+
+        tx.unbounded_send(1).unwrap();
+        assert!(matches!(throttled.next().now_or_never(), Some(Some(Ok(1)))));
+
+        clock.advance(Duration::from_millis(50));
+        tx.unbounded_send(2).unwrap();
+        assert!(matches!(
+            throttled.next().now_or_never(),
+            Some(Some(Err(Throttled(2))))
+        ));
+
+        clock.advance(Duration::from_millis(50));
+        tx.unbounded_send(3).unwrap();
+        assert!(matches!(
+            throttled.next().now_or_never(),
+            Some(Some(Err(Throttled(3))))
+        ));
+
+        // 距离上一个通过的值超过了duration
+        clock.advance(Duration::from_millis(1));
+        tx.unbounded_send(4).unwrap();
+        assert!(matches!(throttled.next().now_or_never(), Some(Some(Ok(4)))));
+
+        drop(tx);
+        assert!(matches!(throttled.next().now_or_never(), Some(None)));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::clock::{Clock, DefaultClock};
+use pin_project::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+pub(crate) use crate::clock::Instant;
+
+// 与`tokio::time::Sleep`相同的接口和行为，由`DefaultClock`驱动
+#[pin_project]
+pub(crate) struct Sleep {
+    #[pin]
+    inner: <DefaultClock as Clock>::Sleep,
+    deadline: Instant,
+    // `async_io::Timer`触发后再poll会一直Pending，这里保持Ready直到reset
+    fired: bool,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        if *this.fired {
+            return Poll::Ready(());
+        }
+
+        let poll = this.inner.poll(cx).map(|_| ());
+        *this.fired = poll.is_ready();
+        poll
+    }
+}
+
+impl Sleep {
+    pub(crate) fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    pub(crate) fn reset(self: Pin<&mut Self>, deadline: Instant) {
+        let this = self.project();
+        DefaultClock::default().reset(this.inner, deadline);
+        *this.deadline = deadline;
+        *this.fired = false;
+    }
+}
+
+pub(crate) fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep {
+        inner: DefaultClock::default().sleep_until(deadline),
+        deadline,
+        fired: false,
+    }
+}
+
+pub(crate) fn sleep(duration: Duration) -> Sleep {
+    sleep_until(DefaultClock::default().now() + duration)
+}
+
+// 测试用，在当前后端能驱动计时器的executor上运行
+#[cfg(all(test, feature = "rt-tokio"))]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(future)
+}
+
+#[cfg(all(test, not(feature = "rt-tokio")))]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    futures::executor::block_on(future)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    #[test]
+    fn fired_sleep_stays_ready_until_reset() {
+        block_on(async {
+            let mut delay = Box::pin(sleep(Duration::from_millis(1)));
+            delay.as_mut().await;
+            assert!(delay.as_mut().now_or_never().is_some());
+            assert!(delay.as_mut().now_or_never().is_some());
+
+            delay
+                .as_mut()
+                .reset(Instant::now() + Duration::from_secs(3600));
+            assert!(delay.as_mut().now_or_never().is_none());
+
+            delay
+                .as_mut()
+                .reset(Instant::now() + Duration::from_millis(1));
+            delay.as_mut().await;
+            assert!(delay.as_mut().now_or_never().is_some());
+        });
+    }
+
+    #[test]
+    fn reset_to_same_deadline_after_fired() {
+        block_on(async {
+            let mut delay = Box::pin(sleep(Duration::from_millis(1)));
+            delay.as_mut().await;
+
+            let deadline = delay.deadline();
+            delay.as_mut().reset(deadline);
+            delay.as_mut().await;
+            assert_eq!(delay.deadline(), deadline);
+        });
+    }
+}
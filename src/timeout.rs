@@ -0,0 +1,113 @@
+use crate::time::{sleep, Instant, Sleep};
+use futures::{FutureExt, Stream};
+use pin_project::pin_project;
+use std::fmt;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("gap between items has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TimeoutPolicy {
+    /// 超时后产出`Err(Elapsed)`，并重新开始计时
+    Error,
+    /// 超时后直接结束stream
+    End,
+}
+
+#[pin_project]
+pub struct TimeoutEach<S> {
+    #[pin]
+    stream: S,
+    duration: Duration,
+    first_duration: Option<Duration>,
+    policy: TimeoutPolicy,
+    started: bool,
+    terminated: bool,
+    delay: Pin<Box<Sleep>>,
+}
+
+impl<S: Stream> Stream for TimeoutEach<S> {
+    type Item = Result<S::Item, Elapsed>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let duration = self.duration;
+        let this = self.project();
+        let mut delay = this.delay.as_mut();
+
+        if *this.terminated {
+            return Poll::Ready(None);
+        }
+
+        // 第一次poll时才开始计时
+        if !*this.started {
+            *this.started = true;
+            delay
+                .as_mut()
+                .reset(Instant::now() + this.first_duration.unwrap_or(duration));
+        }
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                delay.reset(Instant::now() + duration);
+                Poll::Ready(Some(Ok(value)))
+            }
+            Poll::Ready(None) => {
+                *this.terminated = true;
+                Poll::Ready(None)
+            }
+            Poll::Pending => {
+                if delay.poll_unpin(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                match this.policy {
+                    TimeoutPolicy::Error => {
+                        delay.reset(Instant::now() + duration);
+                        Poll::Ready(Some(Err(Elapsed)))
+                    }
+                    TimeoutPolicy::End => {
+                        *this.terminated = true;
+                        Poll::Ready(None)
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<S> TimeoutEach<S> {
+    pub fn new(stream: S, duration: Duration) -> Self {
+        Self {
+            stream,
+            duration,
+            first_duration: None,
+            policy: TimeoutPolicy::Error,
+            started: false,
+            terminated: false,
+            delay: Box::pin(sleep(Duration::from_nanos(0))),
+        }
+    }
+
+    /// 超时后的处理方式，默认为`TimeoutPolicy::Error`
+    pub fn with_policy(mut self, policy: TimeoutPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// 第一个值的超时时间，默认与`duration`相同
+    pub fn with_first_timeout(mut self, first_duration: Duration) -> Self {
+        self.first_duration = Some(first_duration);
+        self
+    }
+}
@@ -0,0 +1,65 @@
+use crate::time::Instant;
+use futures::Stream;
+use pin_project::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[pin_project]
+pub struct Timestamp<S> {
+    #[pin]
+    stream: S,
+}
+
+impl<S: Stream> Stream for Timestamp<S> {
+    type Item = (Instant, S::Item);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.project()
+            .stream
+            .poll_next(cx)
+            .map(|value| value.map(|value| (Instant::now(), value)))
+    }
+}
+
+impl<S> Timestamp<S> {
+    pub fn new(stream: S) -> Self {
+        Self { stream }
+    }
+}
+
+#[pin_project]
+pub struct TimeInterval<S> {
+    #[pin]
+    stream: S,
+    last_time: Option<Instant>,
+}
+
+impl<S: Stream> Stream for TimeInterval<S> {
+    type Item = (Duration, S::Item);
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        // 第一个值的间隔从第一次poll开始计算
+        let last_time = *this.last_time.get_or_insert_with(Instant::now);
+
+        match this.stream.poll_next(cx) {
+            Poll::Ready(Some(value)) => {
+                let now = Instant::now();
+                *this.last_time = Some(now);
+                Poll::Ready(Some((now - last_time, value)))
+            }
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<S> TimeInterval<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            last_time: None,
+        }
+    }
+}
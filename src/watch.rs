@@ -0,0 +1,53 @@
+use futures::{Stream, StreamExt};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// drop时停止驱动stream的任务
+#[derive(Debug)]
+pub struct WatchGuard {
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WatchGuard {
+    /// 不再在drop时停止任务，任务会一直运行到stream结束或所有receiver被drop
+    pub fn detach(mut self) -> JoinHandle<()> {
+        self.handle.take().expect("WatchGuard already detached")
+    }
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+pub fn into_watch<S>(stream: S, initial: S::Item) -> (watch::Receiver<S::Item>, WatchGuard)
+where
+    S: Stream + Send + 'static,
+    S::Item: Send + Sync + 'static,
+{
+    let (tx, rx) = watch::channel(initial);
+
+    let handle = tokio::spawn(async move {
+        let mut stream = Box::pin(stream);
+        loop {
+            tokio::select! {
+                // 所有receiver都drop了就不再驱动stream
+                _ = tx.closed() => break,
+                next = stream.next() => match next {
+                    Some(value) => {
+                        tx.send_replace(value);
+                    }
+                    None => break,
+                },
+            }
+        }
+    });
+
+    let guard = WatchGuard {
+        handle: Some(handle),
+    };
+    (rx, guard)
+}